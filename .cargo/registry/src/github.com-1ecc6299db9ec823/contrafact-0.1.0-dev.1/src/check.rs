@@ -1,14 +1,380 @@
-/// The result of a check operation, which contains an error message for every
-/// constraint which was not met.
-//
-// TODO: add ability to abort, so that further checks will not occur
-#[derive(Debug, Clone, PartialEq, Eq, Hash, derive_more::From, derive_more::IntoIterator)]
+/// How seriously a [`Failure`] should be taken.
+///
+/// A `Warning` is reported the same way an `Error` is, but does not make
+/// [`Check::is_err`] return `true`, so callers (e.g. [`check_seq`](crate::check_seq))
+/// can surface soft constraints without hard-failing on them.
+///
+/// `Fatal` is like `Error`, but also makes [`Check::is_fatal`] return `true`.
+/// Combinators that fold several `Check`s together (e.g. `facts!`'s `Vec<F>`/
+/// `&mut [F]` impl, [`check_seq`](crate::check_seq)) stop evaluating further
+/// facts once they see one, rather than piling on errors that are probably
+/// meaningless once an outer invariant is already broken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    /// The constraint was violated; the check should be considered failed.
+    Error,
+    /// The constraint was violated, but only softly: the check should still
+    /// be considered passed.
+    Warning,
+    /// The constraint was violated badly enough that further checks should
+    /// not be attempted.
+    Fatal,
+}
+
+impl Severity {
+    /// Higher means more serious; used to find the worst severity in a tree
+    /// of failures without needing `Ord` on the public enum itself (its
+    /// variant order above is chosen for readability, not severity).
+    fn rank(self) -> u8 {
+        match self {
+            Severity::Warning => 0,
+            Severity::Error => 1,
+            Severity::Fatal => 2,
+        }
+    }
+}
+
+/// One step of a structured path to a [`Failure`]: either a named field or
+/// an index into a collection. Attached by [`Check::push_context`]/
+/// [`Check::prefix`], which [`lens`](crate::lens)/[`prism`](crate::prism)
+/// and [`check_seq`](crate::check_seq) use so that a failure deep inside a
+/// struct with many fields (e.g. a Holochain entry type) can be traced back
+/// to exactly which field or sequence item it came from, without making
+/// every inner fact hand-format its own prefix.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Segment {
+    /// A named struct/enum field, e.g. `nickname`.
+    Field(String),
+    /// An index into a collection, e.g. `3`.
+    Index(usize),
+}
+
+impl From<String> for Segment {
+    fn from(name: String) -> Self {
+        Segment::Field(name)
+    }
+}
+
+impl From<&str> for Segment {
+    fn from(name: &str) -> Self {
+        Segment::Field(name.to_string())
+    }
+}
+
+impl From<usize> for Segment {
+    fn from(index: usize) -> Self {
+        Segment::Index(index)
+    }
+}
+
+/// A single failure produced by checking a (possibly deeply nested) fact.
+///
+/// `path` records the chain of combinator labels the failure passed through
+/// on its way out, outermost first, e.g. `["lens(x)", "lens(y)"]`, so that
+/// callers can branch on *which* field failed instead of just parsing a
+/// formatted string. See [`Check::prefix_path`].
+///
+/// `segments` is a parallel, more precisely-typed record of the same
+/// journey: each [`Segment`] is a bare field name or collection index
+/// rather than a decorated combinator label. See [`Check::push_context`]
+/// and [`Check::result_structured`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Failure {
+    /// The chain of combinator labels this failure passed through, outermost
+    /// first.
+    pub path: Vec<String>,
+    /// The chain of fields/indices this failure passed through, outermost
+    /// first.
+    pub segments: Vec<Segment>,
+    /// The failure message itself.
+    pub message: String,
+    /// How seriously this failure should be taken.
+    pub severity: Severity,
+}
+
+impl Failure {
+    pub(crate) fn new(message: String) -> Self {
+        Self {
+            path: Vec::new(),
+            segments: Vec::new(),
+            message,
+            severity: Severity::Error,
+        }
+    }
+
+    /// Render `segments` as a JSON-Pointer-style path, e.g. `/profile/nickname`
+    /// or `/items/3`. Empty if no segment was ever attached.
+    pub fn pointer(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Field(name) => format!("/{}", name),
+                Segment::Index(index) => format!("/{}", index),
+            })
+            .collect()
+    }
+
+    /// Render `segments` as a dotted path, e.g. `profile.nickname` or
+    /// `items[3]`. Empty if no segment was ever attached.
+    fn dotted_path(&self) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Field(name) => {
+                    if !out.is_empty() {
+                        out.push('.');
+                    }
+                    out.push_str(name);
+                }
+                Segment::Index(index) => {
+                    out.push('[');
+                    out.push_str(&index.to_string());
+                    out.push(']');
+                }
+            }
+        }
+        out
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// A node in the labeled failure tree produced by checking a (possibly
+/// nested) [`Fact`](crate::Fact).
+///
+/// Most facts only ever produce [`Leaf`](CheckTree::Leaf) nodes. The
+/// remaining variants are produced by combinators which wrap an inner fact,
+/// so that the combinator's own context is retained instead of being
+/// collapsed into a single pre-formatted string: `not` can show the tree it
+/// was negating, `or` can show both of its branches, `lens`/`prism`/
+/// [`check_seq`](crate::check_seq) can show which field/item a nested
+/// failure came from, and so on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CheckTree {
+    /// A failure produced directly by a primitive fact.
+    Leaf(Failure),
+
+    /// Produced by [`not`](crate::not) when the negated fact unexpectedly
+    /// held. `inner` describes what it was that satisfied the negated
+    /// constraint.
+    Not {
+        /// The `not` combinator's own context.
+        context: String,
+        /// What was found to satisfy (and thus violate) the negated fact.
+        inner: Box<CheckTree>,
+    },
+
+    /// Produced by [`or`](crate::or) when neither branch's constraint was
+    /// met. `branches` holds the failure tree of every branch that failed.
+    Or {
+        /// The `or` combinator's own context.
+        context: String,
+        /// The failure tree of each branch.
+        branches: Vec<CheckTree>,
+    },
+
+    /// Produced by [`mapped`](crate::mapped)/[`mapped_fallible`](crate::mapped_fallible).
+    Mapped {
+        /// The `mapped` combinator's own context.
+        reason: String,
+        /// The failure tree of the fact it mapped to.
+        inner: Box<CheckTree>,
+    },
+
+    /// Produced by [`Check::prefix_path`], which [`lens`](crate::lens),
+    /// [`prism`](crate::prism), and [`check_seq`](crate::check_seq) use to
+    /// record which field/item a nested failure came from.
+    Context {
+        /// The label for this level of context, e.g. `"lens(x)"` or
+        /// `"item 3"`.
+        label: String,
+        /// The failure tree found within this context.
+        inner: Box<CheckTree>,
+    },
+}
+
+impl CheckTree {
+    /// Render this node, and its children, into the same flat string format
+    /// that `Check` has always produced, e.g. `"lens(x) > mapped(y) > message"`.
+    pub fn render(&self) -> String {
+        match self {
+            CheckTree::Leaf(failure) => failure.to_string(),
+            CheckTree::Not { context, inner } => {
+                format!("not({}) > {}", context, inner.render())
+            }
+            CheckTree::Or { context, branches } => format!(
+                "expected one of the following conditions to be met ({}): {}",
+                context,
+                branches
+                    .iter()
+                    .map(CheckTree::render)
+                    .collect::<Vec<_>>()
+                    .join(" OR ")
+            ),
+            CheckTree::Mapped { reason, inner } => {
+                format!("mapped({}) > {}", reason, inner.render())
+            }
+            CheckTree::Context { label, inner } => format!("{} > {}", label, inner.render()),
+        }
+    }
+
+    /// The most serious [`Severity`] found anywhere in this tree.
+    fn max_severity(&self) -> Severity {
+        match self {
+            CheckTree::Leaf(failure) => failure.severity,
+            CheckTree::Not { inner, .. }
+            | CheckTree::Mapped { inner, .. }
+            | CheckTree::Context { inner, .. } => inner.max_severity(),
+            CheckTree::Or { branches, .. } => branches
+                .iter()
+                .map(CheckTree::max_severity)
+                .max_by_key(|s| s.rank())
+                .unwrap_or(Severity::Warning),
+        }
+    }
+
+    /// Whether this node (or any of its children) is an [`Error`](Severity::Error)
+    /// (or worse) failure, as opposed to only [`Warning`](Severity::Warning)s.
+    fn has_error(&self) -> bool {
+        self.max_severity().rank() >= Severity::Error.rank()
+    }
+
+    /// Whether this node (or any of its children) is a [`Fatal`](Severity::Fatal)
+    /// failure.
+    fn is_fatal(&self) -> bool {
+        self.max_severity() == Severity::Fatal
+    }
+
+    /// Every [`Failure`] contained anywhere in this tree, ignoring the
+    /// combinator structure around them: by the time a `Failure` reaches
+    /// here, [`Check::push_context`] has already recorded its full
+    /// `segments` path, so no further tree-walking context is needed.
+    fn failures(&self) -> Vec<&Failure> {
+        match self {
+            CheckTree::Leaf(failure) => vec![failure],
+            CheckTree::Not { inner, .. }
+            | CheckTree::Mapped { inner, .. }
+            | CheckTree::Context { inner, .. } => inner.failures(),
+            CheckTree::Or { branches, .. } => {
+                branches.iter().flat_map(CheckTree::failures).collect()
+            }
+        }
+    }
+
+    /// Apply `f` to every [`Failure`] in this tree, preserving its shape.
+    fn map_failures(self, f: &impl Fn(Failure) -> Failure) -> CheckTree {
+        match self {
+            CheckTree::Leaf(failure) => CheckTree::Leaf(f(failure)),
+            CheckTree::Not { context, inner } => CheckTree::Not {
+                context,
+                inner: Box::new(inner.map_failures(f)),
+            },
+            CheckTree::Or { context, branches } => CheckTree::Or {
+                context,
+                branches: branches.into_iter().map(|b| b.map_failures(f)).collect(),
+            },
+            CheckTree::Mapped { reason, inner } => CheckTree::Mapped {
+                reason,
+                inner: Box::new(inner.map_failures(f)),
+            },
+            CheckTree::Context { label, inner } => CheckTree::Context {
+                label,
+                inner: Box::new(inner.map_failures(f)),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for CheckTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
+
+/// The result of a check operation, which contains a labeled failure tree
+/// for every constraint which was not met.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, derive_more::IntoIterator)]
 #[must_use = "Check should be used with either `.unwrap()` or `.result()`"]
 pub struct Check {
-    errors: Vec<String>,
+    errors: Vec<CheckTree>,
 }
 
 impl Check {
+    /// Wrap each top-level failure node under a new parent node built by
+    /// `f`. Useful for combinators which add their own context around an
+    /// inner fact's failures while preserving tree structure, rather than
+    /// collapsing it into a single formatted string.
+    pub fn wrap<F>(self, f: F) -> Self
+    where
+        F: Fn(CheckTree) -> CheckTree,
+    {
+        self.errors.into_iter().map(f).collect::<Vec<_>>().into()
+    }
+
+    /// Wrap every top-level failure tree under a [`CheckTree::Context`]
+    /// labeled with `label`, and push that same label onto the front of the
+    /// `path` of every nested [`Failure`], so that the path can also be
+    /// inspected programmatically instead of only being readable as a
+    /// formatted string. This is what `lens`/`prism`/[`check_seq`](crate::check_seq)
+    /// use to record which field/item a nested failure came from.
+    pub fn prefix_path<S: ToString>(self, label: S) -> Self {
+        let label = label.to_string();
+        self.map_failures({
+            let label = label.clone();
+            move |mut failure| {
+                failure.path.insert(0, label.clone());
+                failure
+            }
+        })
+        .wrap(move |inner| CheckTree::Context {
+            label: label.clone(),
+            inner: Box::new(inner),
+        })
+    }
+
+    /// Push `segment` onto the front of the `segments` path of every nested
+    /// [`Failure`], so that [`Check::result_structured`] (and `Check`'s
+    /// `Display` impl) can report precisely which field or collection index
+    /// a nested fact's failure came from, without parsing a rendered
+    /// string. [`lens`](crate::lens)/[`prism`](crate::prism) attach a
+    /// [`Segment::Field`] as they descend into a named field;
+    /// [`check_seq`](crate::check_seq) attaches a [`Segment::Index`] as it
+    /// walks a sequence.
+    ///
+    /// Unlike [`Check::prefix_path`], this does not add a [`CheckTree::Context`]
+    /// node, so it doesn't affect `render()`/`unwrap()`/`result()` output --
+    /// it's purely an additional, structured channel alongside them.
+    pub fn push_context<S: Into<Segment>>(self, segment: S) -> Self {
+        let segment = segment.into();
+        self.map_failures(move |mut failure| {
+            failure.segments.insert(0, segment.clone());
+            failure
+        })
+    }
+
+    /// Alias for [`Check::push_context`], for call sites that read more
+    /// naturally as "prefix this check's failures with `segment`".
+    pub fn prefix<S: Into<Segment>>(self, segment: S) -> Self {
+        self.push_context(segment)
+    }
+
+    /// Apply `f` to every [`Failure`] in this `Check`, preserving the shape
+    /// of its failure tree.
+    pub fn map_failures<F>(self, f: F) -> Self
+    where
+        F: Fn(Failure) -> Failure,
+    {
+        self.errors
+            .into_iter()
+            .map(|t| t.map_failures(&f))
+            .collect::<Vec<_>>()
+            .into()
+    }
+
     /// Map over each error string.
     /// Useful for combinators which add additional context to errors produced
     /// by inner facts.
@@ -27,26 +393,35 @@ impl Check {
     /// Panic if there are any errors, and display those errors.
     pub fn unwrap(self) {
         if !self.errors.is_empty() {
-            let msg = if self.errors.len() == 1 {
-                format!("Check failed: {}", self.errors[0])
+            let rendered: Vec<String> = self.errors.iter().map(CheckTree::render).collect();
+            let msg = if rendered.len() == 1 {
+                format!("Check failed: {}", rendered[0])
             } else {
-                format!("Check failed: {:#?}", self.errors)
+                format!("Check failed: {:#?}", rendered)
             };
             panic!(msg);
         }
     }
 
-    /// There are no errors.
+    /// There are no [`Error`](Severity::Error)-severity failures (`Warning`s
+    /// don't count).
     pub fn is_ok(&self) -> bool {
-        self.errors.is_empty()
+        !self.errors.iter().any(CheckTree::has_error)
     }
 
-    /// There is at least one error.
+    /// There is at least one [`Error`](Severity::Error)-severity failure.
     pub fn is_err(&self) -> bool {
         !self.is_ok()
     }
 
-    /// Convert to a Result: No errors => Ok
+    /// There is at least one [`Fatal`](Severity::Fatal)-severity failure.
+    /// Combinators that fold several `Check`s together should stop
+    /// evaluating further facts once this returns `true`.
+    pub fn is_fatal(&self) -> bool {
+        self.errors.iter().any(CheckTree::is_fatal)
+    }
+
+    /// Convert to a Result: No [`Error`](Severity::Error)-severity failures => Ok
     ///
     /// ```
     /// use contrafact::*;
@@ -57,7 +432,35 @@ impl Check {
         if self.is_ok() {
             std::result::Result::Ok(())
         } else {
-            std::result::Result::Err(self.errors)
+            std::result::Result::Err(self.errors.iter().map(CheckTree::render).collect())
+        }
+    }
+
+    /// Like [`Check::result`], but reports each failure's JSON-Pointer-style
+    /// [`Failure::pointer`] (e.g. `"/profile/nickname"`) paired with its
+    /// message, built from whatever [`Check::push_context`]/[`Check::prefix`]
+    /// calls ran on the way out, instead of one pre-rendered string per
+    /// top-level failure tree.
+    ///
+    /// ```
+    /// use contrafact::*;
+    /// assert_eq!(Check::pass().result_structured(), Ok(()));
+    /// assert_eq!(
+    ///     Check::fail("message").result_structured(),
+    ///     Err(vec![(String::new(), "message".to_string())]),
+    /// );
+    /// ```
+    pub fn result_structured(self) -> std::result::Result<(), Vec<(String, String)>> {
+        if self.is_ok() {
+            std::result::Result::Ok(())
+        } else {
+            std::result::Result::Err(
+                self.errors
+                    .iter()
+                    .flat_map(CheckTree::failures)
+                    .map(|failure| (failure.pointer(), failure.message.clone()))
+                    .collect(),
+            )
         }
     }
 
@@ -80,7 +483,7 @@ impl Check {
     ///
     /// ```
     /// use contrafact::*;
-    /// assert_eq!(Check::pass(), vec![].into())
+    /// assert_eq!(Check::pass(), Vec::<String>::new().into())
     /// ```
     pub fn pass() -> Self {
         Self {
@@ -88,7 +491,7 @@ impl Check {
         }
     }
 
-    /// Create a failure result with a single error.
+    /// Create a failure result with a single, `Error`-severity error.
     ///
     /// ```
     /// use contrafact::*;
@@ -96,24 +499,91 @@ impl Check {
     /// ```
     pub fn fail<S: ToString>(error: S) -> Self {
         Self {
-            errors: vec![error.to_string()],
+            errors: vec![CheckTree::Leaf(Failure::new(error.to_string()))],
+        }
+    }
+
+    /// Create a failure result with a single, `Warning`-severity error: it
+    /// will be reported, but will not make [`Check::is_err`] return `true`.
+    pub fn warn<S: ToString>(warning: S) -> Self {
+        Self {
+            errors: vec![CheckTree::Leaf(Failure {
+                severity: Severity::Warning,
+                ..Failure::new(warning.to_string())
+            })],
+        }
+    }
+
+    /// Create a failure result with a single, `Fatal`-severity error: see
+    /// [`Check::is_fatal`].
+    pub fn fatal<S: ToString>(error: S) -> Self {
+        Self {
+            errors: vec![CheckTree::Leaf(Failure {
+                severity: Severity::Fatal,
+                ..Failure::new(error.to_string())
+            })],
         }
     }
 }
 
+/// Renders each failure as `path.to.field: message` (or just `message` if no
+/// [`Segment`]s were ever attached), joined with `"; "`, e.g.
+/// `"profile.nickname: must be non-empty"`.
+impl std::fmt::Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rendered: Vec<String> = self
+            .errors
+            .iter()
+            .flat_map(CheckTree::failures)
+            .map(|failure| {
+                let path = failure.dotted_path();
+                if path.is_empty() {
+                    failure.message.clone()
+                } else {
+                    format!("{}: {}", path, failure.message)
+                }
+            })
+            .collect();
+        write!(f, "{}", rendered.join("; "))
+    }
+}
+
+impl From<Vec<String>> for Check {
+    fn from(errors: Vec<String>) -> Self {
+        Self {
+            errors: errors
+                .into_iter()
+                .map(|e| CheckTree::Leaf(Failure::new(e)))
+                .collect(),
+        }
+    }
+}
+
+impl From<Vec<CheckTree>> for Check {
+    fn from(errors: Vec<CheckTree>) -> Self {
+        Self { errors }
+    }
+}
+
 type CheckResult = crate::Result<Check>;
 
 impl From<CheckResult> for Check {
     fn from(result: CheckResult) -> Check {
         match result {
             Ok(check) => check,
-            Err(err) => vec![err.to_string()].into(),
+            // An `anyhow` error propagated out of a `check_fallible!` block
+            // usually means the data was too malformed for the rest of the
+            // fact to even make sense of, so promote it straight to `Fatal`
+            // rather than an ordinary error.
+            Err(err) => Check::fatal(err.to_string()),
         }
     }
 }
 
-/// Helper macro to run a check which may produce a Result, mapping any Err into
-/// a normal Check error string.
+/// Helper macro to run a check which may produce a Result, promoting any
+/// propagated `Err` into a `Fatal`-severity [`Check`] (see
+/// [`Check::is_fatal`]), since a `?`-propagated error usually means the data
+/// was too malformed for the rest of the fact to say anything meaningful.
 ///
 /// ```
 /// use contrafact::*;
@@ -123,7 +593,7 @@ impl From<CheckResult> for Check {
 ///     Err(anyhow::Error::msg("message"))?;
 ///     Ok(Check::pass())
 /// }};
-/// assert_eq!(check, Check::fail("message"));
+/// assert_eq!(check, Check::fatal("message"));
 /// ```
 #[macro_export]
 macro_rules! check_fallible {
@@ -135,14 +605,14 @@ macro_rules! check_fallible {
 
 #[cfg(test)]
 mod tests {
-    use crate::Fact;
+    use crate::{Fact, Mutation};
 
     use super::*;
 
     #[test]
     fn test_check_fallible() {
         struct F;
-        impl Fact<()> for F {
+        impl<'a> Fact<'a, ()> for F {
             fn check(&self, _: &()) -> Check {
                 check_fallible! {{
                     let x = 1;
@@ -154,7 +624,12 @@ mod tests {
                 }}
             }
 
-            fn mutate(&self, _: &mut (), _: &mut arbitrary::Unstructured<'static>) {
+            fn mutate(
+                &mut self,
+                _: &mut (),
+                _: &mut arbitrary::Unstructured<'a>,
+                _: &mut Mutation,
+            ) {
                 unimplemented!()
             }
 
@@ -163,4 +638,65 @@ mod tests {
 
         assert_eq!(F.check(&()).result().unwrap_err(), vec!["oh no"]);
     }
+
+    #[test]
+    fn test_check_fallible_is_fatal() {
+        struct F;
+        impl<'a> Fact<'a, ()> for F {
+            fn check(&self, _: &()) -> Check {
+                check_fallible! {{
+                    Err(anyhow::Error::msg("oh no"))?;
+                    Ok(Check::pass())
+                }}
+            }
+
+            fn mutate(
+                &mut self,
+                _: &mut (),
+                _: &mut arbitrary::Unstructured<'a>,
+                _: &mut Mutation,
+            ) {
+                unimplemented!()
+            }
+
+            fn advance(&mut self, _: &()) {}
+        }
+
+        let check = F.check(&());
+        assert!(check.is_err());
+        assert!(check.is_fatal());
+    }
+
+    #[test]
+    fn test_warning_does_not_fail() {
+        let check = Check::warn("just a heads up");
+        assert!(check.is_ok());
+        assert_eq!(check.result(), Ok(()));
+    }
+
+    #[test]
+    fn test_push_context_result_structured_and_display() {
+        let check = Check::fail("must be non-empty")
+            .push_context("nickname")
+            .prefix("profile");
+
+        assert_eq!(check.to_string(), "profile.nickname: must be non-empty");
+        assert_eq!(
+            check.result_structured(),
+            Err(vec![(
+                "/profile/nickname".to_string(),
+                "must be non-empty".to_string()
+            )])
+        );
+    }
+
+    #[test]
+    fn test_push_context_on_index() {
+        let check = Check::fail("must be positive").push_context(3usize);
+        assert_eq!(check.to_string(), "[3]: must be positive");
+        assert_eq!(
+            check.result_structured(),
+            Err(vec![("/3".to_string(), "must be positive".to_string())])
+        );
+    }
 }
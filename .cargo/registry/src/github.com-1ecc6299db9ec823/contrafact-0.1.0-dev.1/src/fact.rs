@@ -1,69 +1,211 @@
 use arbitrary::*;
 
-use crate::Check;
+use crate::{Check, Mutation};
 
-/// When running `Fact::satisfy`, repeat mutate+check this many times, in case
-/// repetition helps ease into the constraint.
+/// When running `Fact::satisfy`, repeat mutate this many times, in case a
+/// single pass isn't enough to reach a fixed point.
 pub(crate) const SATISFY_ATTEMPTS: usize = 3;
 
-/// The trait bounds for the subject of a Fact
-pub trait Bounds: std::fmt::Debug + PartialEq + Arbitrary<'static> + Clone {}
-impl<T> Bounds for T where T: std::fmt::Debug + PartialEq + Arbitrary<'static> + Clone {}
+/// The trait bounds for the subject of a Fact.
+///
+/// The lifetime parameter allows a subject to borrow from the same buffer
+/// that drives generation, rather than forcing every subject (and the
+/// `Unstructured` that mutates it) to be `'static`.
+///
+/// Note that `Clone` is not required: `Fact::mutate` reports whether it
+/// changed the value via a [`Mutation`] accumulator instead of cloning the
+/// subject to compare before and after.
+pub trait Bounds<'a>: std::fmt::Debug + PartialEq + Arbitrary<'a> {}
+impl<'a, T> Bounds<'a> for T where T: std::fmt::Debug + PartialEq + Arbitrary<'a> {}
 
 /// Type alias for a boxed Fact. Implements [`Fact`] itself.
-pub type BoxFact<'a, T> = Box<dyn 'a + Fact<T>>;
+///
+/// Since [`Fact`] has `Send + Sync` as supertraits, `dyn Fact<'a, T>` is
+/// `Send + Sync` automatically, so this box can be handed to a thread pool
+/// (e.g. by [`check_seq_par`](crate::check_seq_par)) without any extra
+/// bounds at the call site.
+pub type BoxFact<'a, T> = Box<dyn 'a + Fact<'a, T>>;
 
 /// Type alias for a Vec of boxed Facts. Implements [`Fact`] itself.
 pub type Facts<'a, T> = Vec<BoxFact<'a, T>>;
 
+/// Same as [`Facts`], but named distinctly for call sites that are
+/// specifically composing facts over data borrowed from a non-`'static`
+/// buffer, to make that intent clear to readers.
+pub type FactsRef<'a, T> = Vec<BoxFact<'a, T>>;
+
+/// Type alias for a boxed [`StatelessFact`]. Implements [`Fact`] itself.
+pub type BoxStatelessFact<'a, T> = Box<dyn 'a + StatelessFact<'a, T>>;
+
+/// Type alias for a Vec of boxed [`StatelessFact`]s. Implements [`Fact`]
+/// itself. This is the type required of the `Facts` returned by the closure
+/// passed to [`mapped`](crate::mapped)/[`mapped_fallible`](crate::mapped_fallible).
+pub type StatelessFacts<'a, T> = Vec<BoxStatelessFact<'a, T>>;
+
+/// A node in the static composition tree of a [`Fact`], describing its
+/// combinator structure (e.g. which `lens`/`prism`/`facts![...]` chain a
+/// failure came from) without needing to run [`Fact::check`] first. See
+/// [`Fact::describe`] and [`to_dot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FactNode {
+    /// A short label identifying this fact or combinator.
+    pub label: String,
+    /// The facts this one is built out of, if any.
+    pub children: Vec<FactNode>,
+}
+
+impl FactNode {
+    /// Construct a childless node.
+    pub fn leaf<S: ToString>(label: S) -> Self {
+        Self {
+            label: label.to_string(),
+            children: Vec::with_capacity(0),
+        }
+    }
+}
+
+/// Render the composition tree of `fact` (see [`Fact::describe`]) as a
+/// Graphviz `digraph`, so that the optic chain behind a failure can be
+/// visualized.
+pub fn to_dot<'a, T, F>(fact: &F) -> String
+where
+    T: Bounds<'a>,
+    F: Fact<'a, T>,
+{
+    fn walk(node: &FactNode, out: &mut String, next_id: &mut usize) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        out.push_str(&format!(
+            "    {} [label=\"{}\"];\n",
+            id,
+            node.label.replace('"', "\\\"")
+        ));
+        for child in &node.children {
+            let child_id = walk(child, out, next_id);
+            out.push_str(&format!("    {} -> {};\n", id, child_id));
+        }
+        id
+    }
+
+    let mut out = String::from("digraph fact {\n");
+    let mut next_id = 0;
+    walk(&fact.describe(), &mut out, &mut next_id);
+    out.push_str("}\n");
+    out
+}
+
 /// A declarative representation of a constraint on some data, which can be
 /// used to both make an assertion (check) or to mold some arbitrary existing
 /// data into a shape which passes that same assertion (mutate)
-pub trait Fact<T>
+///
+/// `Fact`s are required to be `Send + Sync`, the same discipline rule engines
+/// use to allow rules to run across cores: it's what lets
+/// [`check_seq_par`](crate::check_seq_par) hand a fact to a thread pool.
+pub trait Fact<'a, T>: Send + Sync
 where
-    T: Bounds,
+    T: Bounds<'a>,
 {
     /// Assert that the constraint is satisfied (panic if not).
     fn check(&self, obj: &T) -> Check;
 
     /// Apply a mutation which moves the obj closer to satisfying the overall
-    /// constraint.
-    fn mutate(&self, obj: &mut T, u: &mut Unstructured<'static>);
+    /// constraint. Records into `m` whether `obj` was actually changed, and
+    /// any error encountered while trying to satisfy the constraint. This
+    /// lets callers detect a fixed point (no change, no error) without
+    /// cloning `obj` or re-running `check`.
+    fn mutate(&mut self, obj: &mut T, u: &mut Unstructured<'a>, m: &mut Mutation);
 
     /// When checking or mutating a sequence of items, this gets called after
     /// each item to modify the state to get ready for the next item.
     fn advance(&mut self, obj: &T);
 
     /// Mutate a value such that it satisfies the constraint.
-    /// If the constraint cannot be satisfied, panic.
-    fn satisfy(&mut self, obj: &mut T, u: &mut Unstructured<'static>) {
-        let mut last_failure: Vec<String> = vec![];
+    /// If `mutate` records an error (e.g. an unsatisfiable constraint),
+    /// return it as an `Err` rather than panicking, so that callers can
+    /// recover. If the attempts run out without any error being recorded,
+    /// return `Ok` with `obj` as far as it got, which may still fail `check`.
+    fn satisfy(&mut self, obj: &mut T, u: &mut Unstructured<'a>) -> crate::Result<()> {
         for _i in 0..SATISFY_ATTEMPTS {
-            self.mutate(obj, u);
-            if let Err(errs) = self.check(obj).result() {
-                last_failure = errs;
-            } else {
-                return;
+            // Check first, not just after mutating: a `obj` that already
+            // satisfies the constraint must return `Ok` unconditionally,
+            // rather than running `mutate` once more and relying on it to
+            // report "no change" -- a fact built from other facts (e.g.
+            // `or`) can easily report a change on one branch while the
+            // overall constraint was already met, which would otherwise
+            // make this spuriously `Err` even though `obj` was fine.
+            if self.check(obj).is_ok() {
+                return Ok(());
+            }
+            let mut m = Mutation::new();
+            self.mutate(obj, u, &mut m);
+            if m.has_errors() {
+                return Err(anyhow::anyhow!(
+                    "Could not satisfy a constraint: {:?}",
+                    m.errors()
+                ));
+            }
+            if !m.has_changed() {
+                break;
             }
         }
-        panic!(format!(
-            "Could not satisfy a constraint even after {} iterations. Last check failure: {:?}",
-            SATISFY_ATTEMPTS, last_failure
-        ));
+        // Exhausting the attempts (or reaching a fixed point) without
+        // `mutate` ever reporting an error is not itself an error: a fact
+        // whose `mutate` can't fully converge within the budget should
+        // still hand back the value it got to, so callers like `shrink`
+        // can check it themselves and work with a still-failing result.
+        Ok(())
     }
 
     /// Build a new value such that it satisfies the constraint
-    fn build(&mut self, u: &mut Unstructured<'static>) -> T {
+    fn build(&mut self, u: &mut Unstructured<'a>) -> crate::Result<T> {
         let mut obj = T::arbitrary(u).unwrap();
-        self.satisfy(&mut obj, u);
-        obj
+        self.satisfy(&mut obj, u)?;
+        Ok(obj)
+    }
+
+    /// Describe the static structure of this fact, for debugging/
+    /// visualization via [`to_dot`]. The default implementation returns a
+    /// childless node named after the concrete type, so that facts defined
+    /// outside this crate keep compiling without having to implement this
+    /// themselves.
+    fn describe(&self) -> FactNode {
+        FactNode::leaf(std::any::type_name::<Self>())
+    }
+
+    /// Whether this fact's [`advance`](Fact::advance) carries state between
+    /// items in a sequence. [`check_seq_par`](crate::check_seq_par) uses this
+    /// to decide whether a fact graph is safe to check in parallel: a fact
+    /// whose behavior on one item depends on the items that came before it
+    /// cannot be split into independent chunks.
+    ///
+    /// Defaults to `true` so that facts defined outside this crate (whose
+    /// `advance` we know nothing about) are conservatively routed to the
+    /// serial path. Facts that carry no state of their own override this to
+    /// `false`; combinators report whether any fact they wrap is stateful.
+    fn is_stateful(&self) -> bool {
+        true
     }
 }
 
-impl<T, F> Fact<T> for Box<F>
+/// A marker trait for [`Fact`]s whose `advance` is a no-op, i.e. facts with no
+/// internal state that carries over between items in a sequence.
+///
+/// This is required of the `Facts` returned by the closure passed to
+/// [`mapped`](crate::mapped)/[`mapped_fallible`](crate::mapped_fallible),
+/// since those facts are freshly constructed on every call and so cannot
+/// carry state from one item to the next: a stateful fact used this way would
+/// silently have its state reset on every item, which is rarely what's
+/// wanted. Implementing this trait is a promise that no such state exists.
+pub trait StatelessFact<'a, T>: Fact<'a, T>
+where
+    T: Bounds<'a>,
+{
+}
+
+impl<'a, T, F> Fact<'a, T> for Box<F>
 where
-    T: Bounds,
-    F: Fact<T> + ?Sized,
+    T: Bounds<'a>,
+    F: Fact<'a, T> + ?Sized,
 {
     #[tracing::instrument(skip(self))]
     fn check(&self, obj: &T) -> Check {
@@ -71,34 +213,51 @@ where
         (*self).as_ref().check(obj)
     }
 
-    #[tracing::instrument(skip(self, u))]
-    fn mutate(&self, obj: &mut T, u: &mut Unstructured<'static>) {
-        (*self).as_ref().mutate(obj, u);
+    #[tracing::instrument(skip(self, u, m))]
+    fn mutate(&mut self, obj: &mut T, u: &mut Unstructured<'a>, m: &mut Mutation) {
+        (*self).as_mut().mutate(obj, u, m)
     }
 
     #[tracing::instrument(skip(self))]
     fn advance(&mut self, obj: &T) {
         (*self).as_mut().advance(obj)
     }
+
+    fn describe(&self) -> FactNode {
+        (**self).describe()
+    }
+
+    fn is_stateful(&self) -> bool {
+        (**self).is_stateful()
+    }
 }
 
-impl<T, F> Fact<T> for &mut [F]
+impl<'a, T, F> Fact<'a, T> for &mut [F]
 where
-    T: Bounds,
-    F: Fact<T>,
+    T: Bounds<'a>,
+    F: Fact<'a, T>,
 {
     #[tracing::instrument(skip(self))]
     fn check(&self, obj: &T) -> Check {
-        self.iter()
-            .flat_map(|f| f.check(obj))
-            .collect::<Vec<_>>()
-            .into()
+        let mut errors = Vec::new();
+        for f in self.iter() {
+            let check = f.check(obj);
+            let fatal = check.is_fatal();
+            errors.extend(check);
+            if fatal {
+                break;
+            }
+        }
+        errors.into()
     }
 
-    #[tracing::instrument(skip(self, u))]
-    fn mutate(&self, obj: &mut T, u: &mut Unstructured<'static>) {
-        for f in self.iter() {
-            f.mutate(obj, u)
+    #[tracing::instrument(skip(self, u, m))]
+    fn mutate(&mut self, obj: &mut T, u: &mut Unstructured<'a>, m: &mut Mutation) {
+        for f in self.iter_mut() {
+            f.mutate(obj, u, m);
+            if m.has_errors() {
+                break;
+            }
         }
     }
 
@@ -108,25 +267,45 @@ where
             f.advance(obj)
         }
     }
+
+    fn describe(&self) -> FactNode {
+        FactNode {
+            label: "facts".to_string(),
+            children: self.iter().map(Fact::describe).collect(),
+        }
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.iter().any(Fact::is_stateful)
+    }
 }
 
-impl<T, F> Fact<T> for Vec<F>
+impl<'a, T, F> Fact<'a, T> for Vec<F>
 where
-    T: Bounds,
-    F: Fact<T>,
+    T: Bounds<'a>,
+    F: Fact<'a, T>,
 {
     #[tracing::instrument(skip(self))]
     fn check(&self, obj: &T) -> Check {
-        self.iter()
-            .flat_map(|f| f.check(obj))
-            .collect::<Vec<_>>()
-            .into()
+        let mut errors = Vec::new();
+        for f in self.iter() {
+            let check = f.check(obj);
+            let fatal = check.is_fatal();
+            errors.extend(check);
+            if fatal {
+                break;
+            }
+        }
+        errors.into()
     }
 
-    #[tracing::instrument(skip(self, u))]
-    fn mutate(&self, obj: &mut T, u: &mut Unstructured<'static>) {
-        for f in self.iter() {
-            f.mutate(obj, u)
+    #[tracing::instrument(skip(self, u, m))]
+    fn mutate(&mut self, obj: &mut T, u: &mut Unstructured<'a>, m: &mut Mutation) {
+        for f in self.iter_mut() {
+            f.mutate(obj, u, m);
+            if m.has_errors() {
+                break;
+            }
         }
     }
 
@@ -136,4 +315,36 @@ where
             f.advance(obj)
         }
     }
+
+    fn describe(&self) -> FactNode {
+        FactNode {
+            label: "facts".to_string(),
+            children: self.iter().map(Fact::describe).collect(),
+        }
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.iter().any(Fact::is_stateful)
+    }
+}
+
+impl<'a, T, F> StatelessFact<'a, T> for Box<F>
+where
+    T: Bounds<'a>,
+    F: StatelessFact<'a, T> + ?Sized,
+{
+}
+
+impl<'a, T, F> StatelessFact<'a, T> for &mut [F]
+where
+    T: Bounds<'a>,
+    F: StatelessFact<'a, T>,
+{
+}
+
+impl<'a, T, F> StatelessFact<'a, T> for Vec<F>
+where
+    T: Bounds<'a>,
+    F: StatelessFact<'a, T>,
+{
 }
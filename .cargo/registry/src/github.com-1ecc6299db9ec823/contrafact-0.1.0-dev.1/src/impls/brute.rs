@@ -2,14 +2,16 @@ use std::sync::Arc;
 
 use arbitrary::Unstructured;
 
-use crate::{check_fallible, fact::Bounds, Check, Fact, BRUTE_ITERATION_LIMIT};
+use crate::{
+    check_fallible, fact::Bounds, Check, Fact, Mutation, StatelessFact, BRUTE_ITERATION_LIMIT,
+};
 
 /// A version of [`brute`] whose closure returns a Result
 pub fn brute_fallible<T, F, S>(reason: S, f: F) -> BruteFact<'static, T>
 where
     S: ToString,
-    T: Bounds,
-    F: 'static + Fn(&T) -> crate::Result<bool>,
+    T: Bounds<'static>,
+    F: 'static + Send + Sync + Fn(&T) -> crate::Result<bool>,
 {
     BruteFact::<'static, T>::new(reason.to_string(), f)
 }
@@ -32,7 +34,10 @@ where
 /// constraint, the mutation may drastically alter the data, potentially undoing
 /// constraints that were met by previous mutations.
 ///
-/// There is a fixed iteration limit, beyond which this will panic.
+/// There is an iteration limit (see [`BruteFact::with_limit`]), beyond which mutation gives up and
+/// records an error on the `Mutation` accumulator (surfaced by
+/// [`Fact::satisfy`](crate::Fact::satisfy)/[`Fact::build`](crate::Fact::build)
+/// as an `Err`) rather than panicking.
 ///
 /// ```
 /// use arbitrary::Unstructured;
@@ -43,53 +48,155 @@ where
 /// }
 ///
 /// let mut u = Unstructured::new(&[0; 9999]);
-/// assert!(div_by(3).build(&mut u) % 3 == 0);
+/// assert!(div_by(3).build(&mut u).unwrap() % 3 == 0);
 /// ```
 pub fn brute<T, F, S>(reason: S, f: F) -> BruteFact<'static, T>
 where
     S: ToString,
-    T: Bounds,
-    F: 'static + Fn(&T) -> bool,
+    T: Bounds<'static>,
+    F: 'static + Send + Sync + Fn(&T) -> bool,
 {
     BruteFact::<'static, T>::new(reason.to_string(), move |x| Ok(f(x)))
 }
 
+/// How [`BruteFact::mutate`] should pick its next candidate value after a
+/// failed attempt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BruteStrategy {
+    /// Replace `t` with a brand new `T::arbitrary(u)` on every failed
+    /// attempt, win or lose. This is the default, and matches the original
+    /// behavior of this fact.
+    Resample,
+    /// Like `Resample` while still searching, but if the iteration limit is
+    /// reached without finding a satisfying value, restore `t` to whatever
+    /// it was before this fact started mutating it, instead of leaving it
+    /// at the last (failing) candidate.
+    ///
+    /// `Bounds` doesn't require `Clone`, so a true "perturb only the slice
+    /// of `u` this constraint consumes, leave everything else alone"
+    /// strategy isn't possible for a fully generic `T`. This is the closest
+    /// generic approximation: it stops an unsatisfiable brute fact from
+    /// permanently clobbering constraints a prior fact in the chain already
+    /// met, which is the ordering hazard the docs above warn about.
+    RetainedBase,
+}
+
+impl Default for BruteStrategy {
+    fn default() -> Self {
+        BruteStrategy::Resample
+    }
+}
+
 /// A brute-force fact. Use [`brute()`] to construct.
 #[derive(Clone)]
 pub struct BruteFact<'a, T> {
     reason: String,
-    f: Arc<dyn 'a + Fn(&T) -> crate::Result<bool>>,
+    f: Arc<dyn 'a + Send + Sync + Fn(&T) -> crate::Result<bool>>,
+    limit: usize,
+    strategy: BruteStrategy,
 }
 
-impl<'a, T> Fact<T> for BruteFact<'a, T>
+impl<'a, T> Fact<'a, T> for BruteFact<'a, T>
 where
-    T: Bounds,
+    T: Bounds<'a>,
 {
     fn check(&self, t: &T) -> Check {
         check_fallible!({ Ok(Check::check((self.f)(t)?, self.reason.clone())) })
     }
 
-    fn mutate(&self, t: &mut T, u: &mut Unstructured<'static>) {
-        for _ in 0..BRUTE_ITERATION_LIMIT {
-            if (self.f)(t).expect("TODO: fallible mutation") {
-                return;
+    fn mutate(&mut self, t: &mut T, u: &mut Unstructured<'a>, m: &mut Mutation) {
+        // For `RetainedBase`, leave `t` alone if it already satisfies the
+        // constraint; otherwise capture what `t` was before we start
+        // searching, so it can be restored if the search comes up empty. The
+        // very first candidate is drawn here too, so the loop below doesn't
+        // need to special-case the first iteration except to skip its own
+        // resample.
+        let base: Option<T> = match self.strategy {
+            BruteStrategy::Resample => None,
+            BruteStrategy::RetainedBase => {
+                match (self.f)(t) {
+                    Ok(true) => return,
+                    Ok(false) => (),
+                    Err(err) => {
+                        m.error(format!("brute({}): {}", self.reason, err));
+                        return;
+                    }
+                }
+                match T::arbitrary(u) {
+                    Ok(candidate) => {
+                        m.mark_changed();
+                        Some(std::mem::replace(t, candidate))
+                    }
+                    Err(err) => {
+                        m.error(format!("brute({}): {}", self.reason, err));
+                        return;
+                    }
+                }
+            }
+        };
+
+        for i in 0..self.limit {
+            match (self.f)(t) {
+                Ok(true) => return,
+                Ok(false) => {
+                    if base.is_none() || i > 0 {
+                        *t = match T::arbitrary(u) {
+                            Ok(v) => v,
+                            Err(err) => {
+                                m.error(format!("brute({}): {}", self.reason, err));
+                                return;
+                            }
+                        };
+                    }
+                    m.mark_changed();
+                }
+                Err(err) => {
+                    m.error(format!("brute({}): {}", self.reason, err));
+                    return;
+                }
             }
-            *t = T::arbitrary(u).unwrap();
         }
 
-        panic!(
-            "Exceeded iteration limit of {} while attempting to meet a PredicateFact",
-            BRUTE_ITERATION_LIMIT
-        );
+        if let Some(base) = base {
+            *t = base;
+        }
+        m.error(format!(
+            "brute({}): exceeded iteration limit of {} while attempting to meet the constraint",
+            self.reason, self.limit
+        ));
     }
     fn advance(&mut self, _: &T) {}
+
+    fn is_stateful(&self) -> bool {
+        false
+    }
 }
 
+impl<'a, T> StatelessFact<'a, T> for BruteFact<'a, T> where T: Bounds<'a> {}
+
 impl<'a, T> BruteFact<'a, T> {
-    pub(crate) fn new<F: 'a + Fn(&T) -> crate::Result<bool>>(reason: String, f: F) -> Self {
+    pub(crate) fn new<F: 'a + Send + Sync + Fn(&T) -> crate::Result<bool>>(
+        reason: String,
+        f: F,
+    ) -> Self {
         Self {
             reason,
             f: Arc::new(f),
+            limit: BRUTE_ITERATION_LIMIT,
+            strategy: BruteStrategy::default(),
         }
     }
+
+    /// Raise (or lower) the brute-force search budget from the crate's
+    /// default of [`BRUTE_ITERATION_LIMIT`](crate::BRUTE_ITERATION_LIMIT).
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Pick a search strategy other than the default [`BruteStrategy::Resample`].
+    pub fn with_strategy(mut self, strategy: BruteStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
 }
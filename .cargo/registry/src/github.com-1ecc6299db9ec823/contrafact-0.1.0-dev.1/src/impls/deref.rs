@@ -0,0 +1,82 @@
+use std::ops::DerefMut;
+
+use crate::{fact::*, lens, Check, LensFact, Mutation};
+use arbitrary::Unstructured;
+
+/// Lifts a `Fact` about some type `I` into a `Fact` about any `O` that
+/// derefs to it, e.g. a Shrinkwrap-style newtype wrapper.
+///
+/// This is just [`lens()`] with `DerefMut::deref_mut` as the lens, so that a
+/// `Fact` about the inner value of a single-field wrapper doesn't require
+/// writing out a field-accessing closure.
+///
+/// ```
+/// use contrafact::*;
+/// use arbitrary::*;
+/// use std::ops::{Deref, DerefMut};
+///
+/// #[derive(Debug, Clone, PartialEq, Arbitrary)]
+/// struct Email(String);
+///
+/// impl Deref for Email {
+///     type Target = String;
+///     fn deref(&self) -> &String {
+///         &self.0
+///     }
+/// }
+/// impl DerefMut for Email {
+///     fn deref_mut(&mut self) -> &mut String {
+///         &mut self.0
+///     }
+/// }
+///
+/// let mut fact = deref("Email", eq("must be empty", &String::new()));
+///
+/// assert!(fact.check(&Email("".into())).is_ok());
+/// assert!(fact.check(&Email("x".into())).is_err());
+/// ```
+pub fn deref<'a, O, I, F, S>(label: S, inner_fact: F) -> LensFact<O, I, F>
+where
+    O: Bounds<'a> + DerefMut<Target = I>,
+    I: Bounds<'a>,
+    S: ToString,
+    F: Fact<'a, I>,
+{
+    lens(label, O::deref_mut, inner_fact)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_seq, check_seq, eq, NOISE};
+    use arbitrary::*;
+    use std::ops::Deref;
+
+    #[derive(Debug, Clone, PartialEq, Arbitrary)]
+    struct Wrapper(u32);
+
+    impl Deref for Wrapper {
+        type Target = u32;
+        fn deref(&self) -> &u32 {
+            &self.0
+        }
+    }
+    impl DerefMut for Wrapper {
+        fn deref_mut(&mut self) -> &mut u32 {
+            &mut self.0
+        }
+    }
+
+    #[test]
+    fn test() {
+        observability::test_run().ok();
+        let mut u = Unstructured::new(&NOISE);
+
+        let f = || deref("Wrapper", eq("must be 1", &1));
+
+        let ones = build_seq(&mut u, 3, f()).unwrap();
+        check_seq(ones.as_slice(), f()).unwrap();
+
+        assert!(ones.iter().all(|w| w.0 == 1));
+    }
+}
@@ -1,6 +1,6 @@
 use std::{marker::PhantomData, sync::Arc};
 
-use crate::{fact::*, Check};
+use crate::{fact::*, Check, Mutation};
 use arbitrary::Unstructured;
 
 /// Lifts a Fact about a subset of some data into a Fact about the superset.
@@ -31,34 +31,29 @@ use arbitrary::Unstructured;
 /// assert!(fact.check(&S {x: 2, y: 333}).is_err());
 ///
 /// let mut u = Unstructured::new(&[0; 9999]);
-/// let a = fact.build(&mut u);
+/// let a = fact.build(&mut u).unwrap();
 /// assert_eq!(a.x, 1);
 /// ```
 //
 // TODO: can rewrite this in terms of PrismFact for DRYness
-pub fn lens<O, T, F, L, S>(label: S, lens: L, inner_fact: F) -> LensFact<O, T, F>
+pub fn lens<'a, O, T, F, L, S>(label: S, lens: L, inner_fact: F) -> LensFact<O, T, F>
 where
-    O: Bounds,
-    T: Bounds,
+    O: Bounds<'a>,
+    T: Bounds<'a>,
     S: ToString,
-    F: Fact<T>,
-    L: 'static + Fn(&mut O) -> &mut T,
+    F: Fact<'a, T>,
+    L: 'static + Send + Sync + Fn(&mut O) -> &mut T,
 {
     LensFact::new(label.to_string(), lens, inner_fact)
 }
 
 /// A fact which uses a lens to apply another fact. Use [`lens()`] to construct.
 #[derive(Clone)]
-pub struct LensFact<O, T, F>
-where
-    T: Bounds,
-    O: Bounds,
-    F: Fact<T>,
-{
+pub struct LensFact<O, T, F> {
     label: String,
 
     /// Function which maps outer structure to inner substructure
-    lens: Arc<dyn 'static + Fn(&mut O) -> &mut T>,
+    lens: Arc<dyn 'static + Send + Sync + Fn(&mut O) -> &mut T>,
 
     /// The inner_fact about the inner substructure
     inner_fact: F,
@@ -66,19 +61,14 @@ where
     __phantom: PhantomData<F>,
 }
 
-impl<O, T, F> LensFact<O, T, F>
-where
-    T: Bounds,
-    O: Bounds,
-    F: Fact<T>,
-{
+impl<O, T, F> LensFact<O, T, F> {
     /// Constructor. Supply a lens and an existing Fact to create a new Fact.
-    pub fn new<L>(label: String, lens: L, inner_fact: F) -> Self
+    pub fn new<'a, L>(label: String, lens: L, inner_fact: F) -> Self
     where
-        T: Bounds,
-        O: Bounds,
-        F: Fact<T>,
-        L: 'static + Fn(&mut O) -> &mut T,
+        T: Bounds<'a>,
+        O: Bounds<'a>,
+        F: Fact<'a, T>,
+        L: 'static + Send + Sync + Fn(&mut O) -> &mut T,
     {
         Self {
             label,
@@ -89,11 +79,11 @@ where
     }
 }
 
-impl<O, T, F> Fact<O> for LensFact<O, T, F>
+impl<'a, O, T, F> Fact<'a, O> for LensFact<O, T, F>
 where
-    T: Bounds,
-    O: Bounds,
-    F: Fact<T>,
+    T: Bounds<'a>,
+    O: Bounds<'a>,
+    F: Fact<'a, T>,
 {
     #[tracing::instrument(skip(self))]
     fn check(&self, obj: &O) -> Check {
@@ -105,13 +95,14 @@ where
             let o = o as *mut O;
             self.inner_fact
                 .check((self.lens)(&mut *o))
-                .map(|err| format!("lens({}) > {}", self.label, err))
+                .prefix_path(format!("lens({})", self.label))
+                .push_context(self.label.clone())
         }
     }
 
-    #[tracing::instrument(skip(self, u))]
-    fn mutate(&self, obj: &mut O, u: &mut Unstructured<'static>) {
-        self.inner_fact.mutate((self.lens)(obj), u)
+    #[tracing::instrument(skip(self, u, m))]
+    fn mutate(&mut self, obj: &mut O, u: &mut Unstructured<'a>, m: &mut Mutation) {
+        self.inner_fact.mutate((self.lens)(obj), u, m)
     }
 
     #[tracing::instrument(skip(self))]
@@ -125,6 +116,25 @@ where
             self.inner_fact.advance((self.lens)(&mut *o))
         }
     }
+
+    fn describe(&self) -> FactNode {
+        FactNode {
+            label: format!("lens({})", self.label),
+            children: vec![self.inner_fact.describe()],
+        }
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.inner_fact.is_stateful()
+    }
+}
+
+impl<'a, O, T, F> StatelessFact<'a, O> for LensFact<O, T, F>
+where
+    T: Bounds<'a>,
+    O: Bounds<'a>,
+    F: StatelessFact<'a, T>,
+{
 }
 
 #[cfg(test)]
@@ -146,7 +156,7 @@ mod tests {
 
         let f = || lens("S::x", |s: &mut S| &mut s.x, eq("must be 1", &1));
 
-        let ones = build_seq(&mut u, 3, f());
+        let ones = build_seq(&mut u, 3, f()).unwrap();
         check_seq(ones.as_slice(), f()).unwrap();
 
         assert!(ones.iter().all(|s| s.x == 1));
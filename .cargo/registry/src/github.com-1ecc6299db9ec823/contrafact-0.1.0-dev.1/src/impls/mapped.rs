@@ -2,14 +2,14 @@ use std::sync::Arc;
 
 use arbitrary::Unstructured;
 
-use crate::{check_fallible, fact::Bounds, Check, Fact, Facts};
+use crate::{check_fallible, fact::Bounds, Check, CheckTree, Fact, Mutation, StatelessFacts};
 
 /// A version of [`mapped`] whose closure returns a Result
 pub fn mapped_fallible<'a, T, F, S>(reason: S, f: F) -> MappedFact<'a, T>
 where
     S: ToString,
-    T: Bounds,
-    F: 'static + Fn(&T) -> crate::Result<Facts<'a, T>>,
+    T: Bounds<'a>,
+    F: 'static + Send + Sync + Fn(&T) -> crate::Result<StatelessFacts<'a, T>>,
 {
     MappedFact::new(reason.to_string(), f)
 }
@@ -24,9 +24,10 @@ where
 /// explicitly construct the value.
 ///
 /// **NOTE**: since the returned Facts are generated brand-new on-the-fly,
-/// these Facts must be stateless. State changes cannot be carried over to
-/// subsequent calls when running over a sequence.
-/// (TODO: add `StatelessFact` trait to give type-level protection here.)
+/// these Facts must be stateless: the closure returns a [`StatelessFacts`],
+/// which only accepts facts whose `advance` is a no-op, so that state
+/// changes can't silently fail to carry over to subsequent calls when
+/// running over a sequence.
 ///
 /// ```
 /// use contrafact::*;
@@ -37,9 +38,9 @@ where
 /// //    and otherwise, ensure that it's divisible by 10"
 /// let fact = mapped("reason", |n: &u32| {
 ///     if *n > 9000 {
-///         facts![ brute("divisible by 9", |n| *n % 9 == 0) ]
+///         stateless_facts![ brute("divisible by 9", |n| *n % 9 == 0) ]
 ///     } else {
-///         facts![ brute("divisible by 10", |n| *n % 10 == 0) ]
+///         stateless_facts![ brute("divisible by 10", |n| *n % 10 == 0) ]
 ///     }
 /// });
 ///
@@ -51,8 +52,8 @@ where
 pub fn mapped<T, F, S>(reason: S, f: F) -> MappedFact<'static, T>
 where
     S: ToString,
-    T: Bounds,
-    F: 'static + Fn(&T) -> Facts<'static, T>,
+    T: Bounds<'static>,
+    F: 'static + Send + Sync + Fn(&T) -> StatelessFacts<'static, T>,
 {
     MappedFact::new(reason.to_string(), move |x| Ok(f(x)))
 }
@@ -62,30 +63,43 @@ where
 #[derive(Clone)]
 pub struct MappedFact<'a, T> {
     reason: String,
-    f: Arc<dyn 'a + Fn(&T) -> crate::Result<Facts<'a, T>>>,
+    f: Arc<dyn 'a + Send + Sync + Fn(&T) -> crate::Result<StatelessFacts<'a, T>>>,
 }
 
-impl<'a, T> Fact<T> for MappedFact<'a, T>
+impl<'a, T> Fact<'a, T> for MappedFact<'a, T>
 where
-    T: Bounds,
+    T: Bounds<'a>,
 {
     fn check(&self, t: &T) -> Check {
         check_fallible! {{
-            Ok((self.f)(t)?
-            .check(t)
-            .map(|e| format!("mapped({}) > {}", self.reason, e)))
+            Ok((self.f)(t)?.check(t).wrap(|inner| CheckTree::Mapped {
+                reason: self.reason.clone(),
+                inner: Box::new(inner),
+            }))
         }}
     }
 
-    fn mutate(&self, t: &mut T, u: &mut Unstructured<'static>) {
-        (self.f)(t).expect("TODO: fallible mutation").mutate(t, u)
+    fn mutate(&mut self, t: &mut T, u: &mut Unstructured<'a>, m: &mut Mutation) {
+        match (self.f)(t) {
+            Ok(mut facts) => facts.mutate(t, u, m),
+            Err(e) => m.error(e.to_string()),
+        }
     }
 
     fn advance(&mut self, _: &T) {}
+
+    fn is_stateful(&self) -> bool {
+        false
+    }
 }
 
+impl<'a, T> crate::StatelessFact<'a, T> for MappedFact<'a, T> where T: Bounds<'a> {}
+
 impl<'a, T> MappedFact<'a, T> {
-    pub(crate) fn new<F: 'a + Fn(&T) -> crate::Result<Facts<'a, T>>>(reason: String, f: F) -> Self {
+    pub(crate) fn new<F: 'a + Send + Sync + Fn(&T) -> crate::Result<StatelessFacts<'a, T>>>(
+        reason: String,
+        f: F,
+    ) -> Self {
         Self {
             reason,
             f: Arc::new(f),
@@ -107,7 +121,7 @@ fn test_mapped_fact() {
     //     then the second element must be divisible by 4.
     let divisibility_fact = || {
         mapped("reason", |t: &T| {
-            facts![lens(
+            stateless_facts![lens(
                 "T.1",
                 |(_, n)| n,
                 if t.0 % 2 == 0 {
@@ -123,10 +137,10 @@ fn test_mapped_fact() {
             .result()
             .unwrap_err()),
         vec![
-            "item 0: mapped(reason) > lens(T.1) > divisible by 4".to_string(),
-            "item 1: mapped(reason) > lens(T.1) > divisible by 3".to_string(),
-            "item 2: mapped(reason) > lens(T.1) > divisible by 4".to_string(),
-            "item 3: mapped(reason) > lens(T.1) > divisible by 3".to_string(),
+            "item 0 > mapped(reason) > lens(T.1) > divisible by 4".to_string(),
+            "item 1 > mapped(reason) > lens(T.1) > divisible by 3".to_string(),
+            "item 2 > mapped(reason) > lens(T.1) > divisible by 4".to_string(),
+            "item 3 > mapped(reason) > lens(T.1) > divisible by 3".to_string(),
         ]
     );
 
@@ -139,7 +153,7 @@ fn test_mapped_fact() {
         ]
     };
 
-    let built = build_seq(&mut u, 12, composite_fact());
+    let built = build_seq(&mut u, 12, composite_fact()).unwrap();
     dbg!(&built);
     check_seq(built.as_slice(), composite_fact()).unwrap();
 }
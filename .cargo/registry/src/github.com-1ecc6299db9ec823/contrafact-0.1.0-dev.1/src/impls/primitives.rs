@@ -3,7 +3,7 @@
 
 use std::{borrow::Borrow, marker::PhantomData};
 
-use crate::{fact::*, Check, BRUTE_ITERATION_LIMIT};
+use crate::{fact::*, Check, CheckTree, Failure, Mutation, Severity, BRUTE_ITERATION_LIMIT};
 
 /// A constraint which is always met
 pub fn always() -> BoolFact {
@@ -63,6 +63,102 @@ where
     ne("___", constant)
 }
 
+/// Specifies a "less than" constraint
+pub fn lt<S, T, B>(context: S, bound: B) -> OrdFact<T, B>
+where
+    S: ToString,
+    T: std::fmt::Debug + PartialOrd,
+    B: Borrow<T>,
+{
+    OrdFact {
+        context: context.to_string(),
+        bound,
+        op: OrdOp::Lt,
+        _phantom: PhantomData,
+    }
+}
+
+/// Specifies a "less than" constraint with no context
+pub fn lt_<T, B>(bound: B) -> OrdFact<T, B>
+where
+    T: std::fmt::Debug + PartialOrd,
+    B: Borrow<T>,
+{
+    lt("___", bound)
+}
+
+/// Specifies a "less than or equal to" constraint
+pub fn le<S, T, B>(context: S, bound: B) -> OrdFact<T, B>
+where
+    S: ToString,
+    T: std::fmt::Debug + PartialOrd,
+    B: Borrow<T>,
+{
+    OrdFact {
+        context: context.to_string(),
+        bound,
+        op: OrdOp::Le,
+        _phantom: PhantomData,
+    }
+}
+
+/// Specifies a "less than or equal to" constraint with no context
+pub fn le_<T, B>(bound: B) -> OrdFact<T, B>
+where
+    T: std::fmt::Debug + PartialOrd,
+    B: Borrow<T>,
+{
+    le("___", bound)
+}
+
+/// Specifies a "greater than" constraint
+pub fn gt<S, T, B>(context: S, bound: B) -> OrdFact<T, B>
+where
+    S: ToString,
+    T: std::fmt::Debug + PartialOrd,
+    B: Borrow<T>,
+{
+    OrdFact {
+        context: context.to_string(),
+        bound,
+        op: OrdOp::Gt,
+        _phantom: PhantomData,
+    }
+}
+
+/// Specifies a "greater than" constraint with no context
+pub fn gt_<T, B>(bound: B) -> OrdFact<T, B>
+where
+    T: std::fmt::Debug + PartialOrd,
+    B: Borrow<T>,
+{
+    gt("___", bound)
+}
+
+/// Specifies a "greater than or equal to" constraint
+pub fn ge<S, T, B>(context: S, bound: B) -> OrdFact<T, B>
+where
+    S: ToString,
+    T: std::fmt::Debug + PartialOrd,
+    B: Borrow<T>,
+{
+    OrdFact {
+        context: context.to_string(),
+        bound,
+        op: OrdOp::Ge,
+        _phantom: PhantomData,
+    }
+}
+
+/// Specifies a "greater than or equal to" constraint with no context
+pub fn ge_<T, B>(bound: B) -> OrdFact<T, B>
+where
+    T: std::fmt::Debug + PartialOrd,
+    B: Borrow<T>,
+{
+    ge("___", bound)
+}
+
 /// Specifies a membership constraint
 pub fn in_iter<'a, I, S, T>(context: S, iter: I) -> InFact<'a, T>
 where
@@ -108,12 +204,12 @@ where
 }
 
 /// Combines two constraints so that either one may be satisfied
-pub fn or<A, B, S, Item>(context: S, a: A, b: B) -> OrFact<A, B, Item>
+pub fn or<'a, A, B, S, Item>(context: S, a: A, b: B) -> OrFact<A, B, Item>
 where
     S: ToString,
-    A: Fact<Item>,
-    B: Fact<Item>,
-    Item: Bounds,
+    A: Fact<'a, Item>,
+    B: Fact<'a, Item>,
+    Item: Bounds<'a>,
 {
     OrFact {
         context: context.to_string(),
@@ -130,8 +226,8 @@ where
 pub fn not<'a, F, S, T>(context: S, fact: F) -> NotFact<F, T>
 where
     S: ToString,
-    F: Fact<T>,
-    T: Bounds,
+    F: Fact<'a, T>,
+    T: Bounds<'a>,
 {
     NotFact {
         context: context.to_string(),
@@ -143,18 +239,36 @@ where
 /// Negates a fact, with no context given
 pub fn not_<'a, F, T>(fact: F) -> NotFact<F, T>
 where
-    F: Fact<T>,
-    T: Bounds,
+    F: Fact<'a, T>,
+    T: Bounds<'a>,
 {
     not("___", fact)
 }
 
+/// Runs `fact`, but demotes any failures it produces to
+/// [`Warning`](Severity::Warning) severity: they'll still be reported by
+/// [`Check::unwrap`]/rendering, but won't make [`Check::is_err`] return
+/// `true`. Useful for soft constraints that should be surfaced without
+/// hard-failing the overall check.
+pub fn warn<'a, F, S, T>(context: S, fact: F) -> WarnFact<F, T>
+where
+    S: ToString,
+    F: Fact<'a, T>,
+    T: Bounds<'a>,
+{
+    WarnFact {
+        context: context.to_string(),
+        fact,
+        _phantom: PhantomData,
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BoolFact(bool, String);
 
-impl<T> Fact<T> for BoolFact
+impl<'a, T> Fact<'a, T> for BoolFact
 where
-    T: Bounds + PartialEq,
+    T: Bounds<'a> + PartialEq,
 {
     fn check(&self, _: &T) -> Check {
         if self.0 {
@@ -165,21 +279,30 @@ where
         .into()
     }
 
-    fn mutate(&self, _: &mut T, _: &mut arbitrary::Unstructured<'static>) {
+    fn mutate(&mut self, _: &mut T, _: &mut arbitrary::Unstructured<'a>, m: &mut Mutation) {
         if !self.0 {
-            panic!("never() cannot be used for mutation.")
+            m.error(format!("never() cannot be used for mutation: {}", self.1));
         }
     }
 
     fn advance(&mut self, _: &T) {}
+
+    fn is_stateful(&self) -> bool {
+        false
+    }
 }
 
+impl<'a, T> StatelessFact<'a, T> for BoolFact where T: Bounds<'a> + PartialEq {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EqFact<T, B> {
     context: String,
     op: EqOp,
     constant: B,
-    _phantom: PhantomData<T>,
+    // `fn() -> T` rather than `T`: `Fact` requires `Send + Sync`, and a bare
+    // `PhantomData<T>` is only `Send`/`Sync` when `T` is, which isn't
+    // guaranteed here. `fn() -> T` is `Send + Sync` unconditionally.
+    _phantom: PhantomData<fn() -> T>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -188,9 +311,9 @@ pub enum EqOp {
     NotEqual,
 }
 
-impl<T, B> Fact<T> for EqFact<T, B>
+impl<'a, T, B> Fact<'a, T> for EqFact<T, B>
 where
-    T: Bounds + PartialEq,
+    T: Bounds<'a> + PartialEq + Clone,
     B: Borrow<T>,
 {
     fn check(&self, obj: &T) -> Check {
@@ -209,23 +332,140 @@ where
         .into()
     }
 
-    fn mutate(&self, obj: &mut T, u: &mut arbitrary::Unstructured<'static>) {
+    fn mutate(&mut self, obj: &mut T, u: &mut arbitrary::Unstructured<'a>, m: &mut Mutation) {
         let constant = self.constant.borrow();
         match self.op {
-            EqOp::Equal => *obj = constant.clone(),
-            EqOp::NotEqual => loop {
-                *obj = T::arbitrary(u).unwrap();
+            EqOp::Equal => {
                 if obj != constant {
-                    break;
+                    *obj = constant.clone();
+                    m.mark_changed();
+                }
+            }
+            EqOp::NotEqual => {
+                if obj == constant {
+                    loop {
+                        *obj = T::arbitrary(u).unwrap();
+                        if obj != constant {
+                            break;
+                        }
+                    }
+                    m.mark_changed();
                 }
-            },
+            }
+        }
+    }
+
+    fn advance(&mut self, _: &T) {}
+
+    fn describe(&self) -> FactNode {
+        let op = match self.op {
+            EqOp::Equal => "eq",
+            EqOp::NotEqual => "ne",
+        };
+        FactNode::leaf(format!("{}: {}", op, self.context))
+    }
+
+    fn is_stateful(&self) -> bool {
+        false
+    }
+}
+
+impl<'a, T, B> StatelessFact<'a, T> for EqFact<T, B>
+where
+    T: Bounds<'a> + PartialEq + Clone,
+    B: Borrow<T>,
+{
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrdFact<T, B> {
+    context: String,
+    op: OrdOp,
+    bound: B,
+    // See the comment on `EqFact::_phantom` for why this is `fn() -> T`.
+    _phantom: PhantomData<fn() -> T>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrdOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl<'a, T, B> Fact<'a, T> for OrdFact<T, B>
+where
+    T: Bounds<'a> + PartialOrd,
+    B: Borrow<T>,
+{
+    fn check(&self, obj: &T) -> Check {
+        let bound = self.bound.borrow();
+        match self.op {
+            OrdOp::Lt if !(obj < bound) => {
+                Check::fail(format!("{}: expected {:?} < {:?}", self.context, obj, bound))
+            }
+            OrdOp::Le if !(obj <= bound) => {
+                Check::fail(format!("{}: expected {:?} <= {:?}", self.context, obj, bound))
+            }
+            OrdOp::Gt if !(obj > bound) => {
+                Check::fail(format!("{}: expected {:?} > {:?}", self.context, obj, bound))
+            }
+            OrdOp::Ge if !(obj >= bound) => {
+                Check::fail(format!("{}: expected {:?} >= {:?}", self.context, obj, bound))
+            }
+            _ => Check::pass(),
+        }
+    }
+
+    fn mutate(&mut self, obj: &mut T, u: &mut arbitrary::Unstructured<'a>, m: &mut Mutation) {
+        fn holds<T: PartialOrd>(op: &OrdOp, obj: &T, bound: &T) -> bool {
+            match op {
+                OrdOp::Lt => obj < bound,
+                OrdOp::Le => obj <= bound,
+                OrdOp::Gt => obj > bound,
+                OrdOp::Ge => obj >= bound,
+            }
+        }
+
+        if holds(&self.op, obj, self.bound.borrow()) {
+            return;
+        }
+        for _ in 0..BRUTE_ITERATION_LIMIT {
+            *obj = T::arbitrary(u).unwrap();
+            if holds(&self.op, obj, self.bound.borrow()) {
+                m.mark_changed();
+                return;
+            }
         }
-        self.check(obj)
-            .result()
-            .expect("there's a bug in EqFact::mutate");
+        m.error(format!(
+            "{}: exceeded iteration limit of {} while searching for a value satisfying the ordering constraint",
+            self.context, BRUTE_ITERATION_LIMIT
+        ));
     }
 
     fn advance(&mut self, _: &T) {}
+
+    fn describe(&self) -> FactNode {
+        let op = match self.op {
+            OrdOp::Lt => "lt",
+            OrdOp::Le => "le",
+            OrdOp::Gt => "gt",
+            OrdOp::Ge => "ge",
+        };
+        FactNode::leaf(format!("{}: {}", op, self.context))
+    }
+
+    fn is_stateful(&self) -> bool {
+        false
+    }
+}
+
+impl<'a, T, B> StatelessFact<'a, T> for OrdFact<T, B>
+where
+    T: Bounds<'a> + PartialOrd,
+    B: Borrow<T>,
+{
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -237,9 +477,9 @@ where
     inner: Vec<&'a T>,
 }
 
-impl<T> Fact<T> for InFact<'_, T>
+impl<'f, T> Fact<'f, T> for InFact<'_, T>
 where
-    T: Bounds,
+    T: Bounds<'f> + Clone,
 {
     fn check(&self, obj: &T) -> Check {
         if self.inner.contains(&obj) {
@@ -253,120 +493,238 @@ where
         .into()
     }
 
-    fn mutate(&self, obj: &mut T, u: &mut arbitrary::Unstructured<'static>) {
-        *obj = (*u.choose(self.inner.as_slice()).unwrap()).to_owned();
-        self.check(obj)
-            .result()
-            .expect("there's a bug in InFact::mutate");
+    fn mutate(&mut self, obj: &mut T, u: &mut arbitrary::Unstructured<'f>, m: &mut Mutation) {
+        // `self.inner` holds `&'a T`, but `obj` here is `&mut T`, so
+        // `contains(&obj)` would compare `&&'a T` against `&&mut T` -- two
+        // different reference kinds that can't unify. Compare the pointees
+        // instead.
+        if !self.inner.iter().any(|x| **x == *obj) {
+            *obj = (*u.choose(self.inner.as_slice()).unwrap()).to_owned();
+            m.mark_changed();
+        }
     }
 
     fn advance(&mut self, _: &T) {}
+
+    fn describe(&self) -> FactNode {
+        FactNode::leaf(format!("in_iter: {}", self.context))
+    }
+
+    fn is_stateful(&self) -> bool {
+        false
+    }
 }
 
+impl<'f, T> StatelessFact<'f, T> for InFact<'_, T> where T: Bounds<'f> + Clone {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ConsecutiveIntFact<T> {
     context: String,
     counter: T,
 }
 
-impl<T> Fact<T> for ConsecutiveIntFact<T>
+impl<'a, T> Fact<'a, T> for ConsecutiveIntFact<T>
 where
-    T: Bounds + num::PrimInt,
+    T: Bounds<'a> + num::PrimInt,
 {
     fn check(&self, obj: &T) -> Check {
         Check::check(*obj == self.counter, self.context.clone())
     }
 
-    fn mutate(&self, obj: &mut T, _: &mut arbitrary::Unstructured<'static>) {
-        *obj = self.counter.clone();
+    fn mutate(&mut self, obj: &mut T, _: &mut arbitrary::Unstructured<'a>, m: &mut Mutation) {
+        if *obj != self.counter {
+            *obj = self.counter.clone();
+            m.mark_changed();
+        }
     }
 
     fn advance(&mut self, _: &T) {
         self.counter = self.counter.checked_add(&T::from(1).unwrap()).unwrap();
     }
+
+    fn describe(&self) -> FactNode {
+        FactNode::leaf(format!("consecutive_int: {}", self.context))
+    }
 }
 
 /// Fact that combines two `Fact`s, returning the OR of the results.
 ///
 /// This is created by the `or` function.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct OrFact<M1, M2, Item>
-where
-    M1: Fact<Item>,
-    M2: Fact<Item>,
-    Item: ?Sized + Bounds,
-{
+pub struct OrFact<M1, M2, Item> {
     context: String,
     pub(crate) a: M1,
     pub(crate) b: M2,
-    _phantom: PhantomData<Item>,
+    // See the comment on `EqFact::_phantom` for why this is `fn() -> Item`.
+    _phantom: PhantomData<fn() -> Item>,
 }
 
-impl<P1, P2, T> Fact<T> for OrFact<P1, P2, T>
+impl<'a, P1, P2, T> Fact<'a, T> for OrFact<P1, P2, T>
 where
-    P1: Fact<T> + Fact<T>,
-    P2: Fact<T> + Fact<T>,
-    T: Bounds,
+    P1: Fact<'a, T>,
+    P2: Fact<'a, T>,
+    T: Bounds<'a>,
 {
     fn check(&self, obj: &T) -> Check {
-        let a = self.a.check(obj).result();
-        let b = self.b.check(obj).result();
-        match (a, b) {
-            (Err(a), Err(b)) => vec![format!(
-                "expected either one of the following conditions to be met:
-condition 1: {:#?}
-condition 2: {:#?}",
-                a, b
-            )]
-            .into(),
-            _ => Check::pass(),
+        let a = self.a.check(obj);
+        let b = self.b.check(obj);
+        if a.is_err() && b.is_err() {
+            let branches = a.into_iter().chain(b.into_iter()).collect();
+            vec![CheckTree::Or {
+                context: self.context.clone(),
+                branches,
+            }]
+            .into()
+        } else {
+            Check::pass()
         }
     }
 
-    fn mutate(&self, obj: &mut T, u: &mut arbitrary::Unstructured<'static>) {
+    fn mutate(&mut self, obj: &mut T, u: &mut arbitrary::Unstructured<'a>, m: &mut Mutation) {
         if *u.choose(&[true, false]).unwrap() {
-            self.a.mutate(obj, u);
+            self.a.mutate(obj, u, m)
         } else {
-            self.b.mutate(obj, u);
+            self.b.mutate(obj, u, m)
         }
     }
 
     fn advance(&mut self, _: &T) {}
+
+    fn describe(&self) -> FactNode {
+        FactNode {
+            label: format!("or({})", self.context),
+            children: vec![self.a.describe(), self.b.describe()],
+        }
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.a.is_stateful() || self.b.is_stateful()
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct NotFact<F, T>
+impl<'a, P1, P2, T> StatelessFact<'a, T> for OrFact<P1, P2, T>
 where
-    F: Fact<T>,
-    T: Bounds,
+    P1: StatelessFact<'a, T>,
+    P2: StatelessFact<'a, T>,
+    T: Bounds<'a>,
 {
+}
+
+#[derive(Debug, Clone)]
+pub struct NotFact<F, T> {
     context: String,
     fact: F,
-    _phantom: PhantomData<T>,
+    // See the comment on `EqFact::_phantom` for why this is `fn() -> T`.
+    _phantom: PhantomData<fn() -> T>,
 }
 
-impl<F, T> Fact<T> for NotFact<F, T>
+impl<'a, F, T> Fact<'a, T> for NotFact<F, T>
 where
-    F: Fact<T>,
-    T: Bounds,
+    F: Fact<'a, T>,
+    T: Bounds<'a>,
 {
     fn check(&self, obj: &T) -> Check {
-        Check::check(
-            self.fact.check(obj).is_err(),
-            format!("not({})", self.context.clone()),
-        )
+        if self.fact.check(obj).is_err() {
+            Check::pass()
+        } else {
+            vec![CheckTree::Not {
+                context: self.context.clone(),
+                inner: Box::new(CheckTree::Leaf(Failure::new(format!(
+                    "{:?} satisfies the negated constraint",
+                    obj
+                )))),
+            }]
+            .into()
+        }
     }
 
-    fn mutate(&self, obj: &mut T, u: &mut arbitrary::Unstructured<'static>) {
+    fn mutate(&mut self, obj: &mut T, u: &mut arbitrary::Unstructured<'a>, m: &mut Mutation) {
+        if self.fact.check(obj).is_err() {
+            return;
+        }
         for _ in 0..BRUTE_ITERATION_LIMIT {
+            *obj = T::arbitrary(u).unwrap();
             if self.fact.check(obj).is_err() {
-                break;
+                m.mark_changed();
+                return;
             }
-            *obj = T::arbitrary(u).unwrap();
         }
+        m.error(format!(
+            "not({}): exceeded iteration limit of {} while searching for a value that violates the inner fact",
+            self.context,
+            BRUTE_ITERATION_LIMIT
+        ));
     }
 
     fn advance(&mut self, _: &T) {}
+
+    fn describe(&self) -> FactNode {
+        FactNode {
+            label: format!("not({})", self.context),
+            children: vec![self.fact.describe()],
+        }
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.fact.is_stateful()
+    }
+}
+
+impl<'a, F, T> StatelessFact<'a, T> for NotFact<F, T>
+where
+    F: StatelessFact<'a, T>,
+    T: Bounds<'a>,
+{
+}
+
+/// Fact that demotes an inner fact's failures to `Warning` severity.
+///
+/// This is created by the `warn` function.
+#[derive(Debug, Clone)]
+pub struct WarnFact<F, T> {
+    context: String,
+    fact: F,
+    // See the comment on `EqFact::_phantom` for why this is `fn() -> T`.
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<'a, F, T> Fact<'a, T> for WarnFact<F, T>
+where
+    F: Fact<'a, T>,
+    T: Bounds<'a>,
+{
+    fn check(&self, obj: &T) -> Check {
+        self.fact.check(obj).map_failures(|mut failure| {
+            failure.severity = Severity::Warning;
+            failure
+        })
+    }
+
+    fn mutate(&mut self, obj: &mut T, u: &mut arbitrary::Unstructured<'a>, m: &mut Mutation) {
+        self.fact.mutate(obj, u, m)
+    }
+
+    fn advance(&mut self, obj: &T) {
+        self.fact.advance(obj)
+    }
+
+    fn describe(&self) -> FactNode {
+        FactNode {
+            label: format!("warn({})", self.context),
+            children: vec![self.fact.describe()],
+        }
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.fact.is_stateful()
+    }
+}
+
+impl<'a, F, T> StatelessFact<'a, T> for WarnFact<F, T>
+where
+    F: StatelessFact<'a, T>,
+    T: Bounds<'a>,
+{
 }
 
 #[cfg(test)]
@@ -382,12 +740,31 @@ mod tests {
 
         let eq1 = eq("must be 1", 1);
 
-        let ones = build_seq(&mut u, 3, eq1.clone());
+        let ones = build_seq(&mut u, 3, eq1.clone()).unwrap();
         check_seq(ones.as_slice(), eq1.clone()).unwrap();
 
         assert!(ones.iter().all(|x| *x == 1));
     }
 
+    #[test]
+    fn test_ord() {
+        observability::test_run().ok();
+        let mut u = Unstructured::new(&NOISE);
+
+        let adult = ge("at least 18", 18u8);
+
+        let adults = build_seq(&mut u, 10, adult.clone()).unwrap();
+        check_seq(adults.as_slice(), adult.clone()).unwrap();
+        assert!(adults.iter().all(|x| *x >= 18));
+
+        assert!(adult.check(&17).is_err());
+        assert!(adult.check(&18).is_ok());
+
+        let minor = lt("under 18", 18u8);
+        assert!(minor.check(&18).is_err());
+        assert!(minor.check(&17).is_ok());
+    }
+
     #[test]
     fn test_or() {
         observability::test_run().ok();
@@ -397,7 +774,7 @@ mod tests {
         let eq2 = eq("must be 2", 2);
         let either = or("can be 1 or 2", eq1, eq2);
 
-        let ones = build_seq(&mut u, 10, either.clone());
+        let ones = build_seq(&mut u, 10, either.clone()).unwrap();
         check_seq(ones.as_slice(), either.clone()).unwrap();
         assert!(ones.iter().all(|x| *x == 1 || *x == 2));
 
@@ -412,9 +789,22 @@ mod tests {
         let eq1 = eq("must be 1", 1);
         let not1 = not_(eq1);
 
-        let nums = build_seq(&mut u, 10, not1.clone());
+        let nums = build_seq(&mut u, 10, not1.clone()).unwrap();
         check_seq(nums.as_slice(), not1.clone()).unwrap();
 
         assert!(nums.iter().all(|x| *x != 1));
     }
+
+    #[test]
+    fn test_warn() {
+        observability::test_run().ok();
+
+        let eq1 = eq("must be 1", 1);
+        let soft = warn("should be 1", eq1);
+
+        // The underlying constraint is violated, but since it's wrapped in
+        // `warn`, the overall check still passes.
+        assert!(soft.check(&2).is_ok());
+        assert_eq!(soft.check(&2).result(), Ok(()));
+    }
 }
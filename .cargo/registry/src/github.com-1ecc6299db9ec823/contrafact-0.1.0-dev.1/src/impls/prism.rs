@@ -1,6 +1,6 @@
 use std::{marker::PhantomData, sync::Arc};
 
-use crate::{fact::*, Check};
+use crate::{fact::*, Check, CheckTree, Mutation};
 use arbitrary::Unstructured;
 
 /// Lifts a Fact about some *optional* subset of data into a Fact about the
@@ -50,7 +50,7 @@ use arbitrary::Unstructured;
 /// assert!(fact.check(&E::Y(99)).is_ok());
 ///
 /// let mut u = Unstructured::new(&[0; 9999]);
-/// let e = fact.build(&mut u);
+/// let e = fact.build(&mut u).unwrap();
 /// match e {
 ///     E::X(x) => assert_eq!(x, 1),
 ///     _ => (),  // Y is not defined by the prism, so it can take on any value.
@@ -60,44 +60,34 @@ use arbitrary::Unstructured;
 /// The `prism` closure is a rather lazy way to provide a prism in the
 /// traditional optics sense. We may consider using a true lens library for
 /// this in the future.
-pub fn prism<O, T, F, P, S>(label: S, prism: P, inner_fact: F) -> PrismFact<O, T, F>
+pub fn prism<'a, O, T, F, P, S>(label: S, prism: P, inner_fact: F) -> PrismFact<O, T, F>
 where
-    O: Bounds,
+    O: Bounds<'a>,
     S: ToString,
-    T: Bounds,
-    F: Fact<T>,
-    P: 'static + Fn(&mut O) -> Option<&mut T>,
+    T: Bounds<'a>,
+    F: Fact<'a, T>,
+    P: 'static + Send + Sync + Fn(&mut O) -> Option<&mut T>,
 {
     PrismFact::new(label.to_string(), prism, inner_fact)
 }
 
 /// A fact which uses a prism to apply another fact. Use [`prism()`] to construct.
 #[derive(Clone)]
-pub struct PrismFact<O, T, F>
-where
-    T: Bounds,
-    O: Bounds,
-    F: Fact<T>,
-{
+pub struct PrismFact<O, T, F> {
     label: String,
-    prism: Arc<dyn 'static + Fn(&mut O) -> Option<&mut T>>,
+    prism: Arc<dyn 'static + Send + Sync + Fn(&mut O) -> Option<&mut T>>,
     inner_fact: F,
     __phantom: PhantomData<F>,
 }
 
-impl<O, T, F> PrismFact<O, T, F>
-where
-    T: Bounds,
-    O: Bounds,
-    F: Fact<T>,
-{
+impl<O, T, F> PrismFact<O, T, F> {
     /// Constructor. Supply a prism and an existing Fact to create a new Fact.
-    pub fn new<P>(label: String, prism: P, inner_fact: F) -> Self
+    pub fn new<'a, P>(label: String, prism: P, inner_fact: F) -> Self
     where
-        T: Bounds,
-        O: Bounds,
-        F: Fact<T>,
-        P: 'static + Fn(&mut O) -> Option<&mut T>,
+        T: Bounds<'a>,
+        O: Bounds<'a>,
+        F: Fact<'a, T>,
+        P: 'static + Send + Sync + Fn(&mut O) -> Option<&mut T>,
     {
         Self {
             label,
@@ -108,11 +98,11 @@ where
     }
 }
 
-impl<O, T, F> Fact<O> for PrismFact<O, T, F>
+impl<'a, O, T, F> Fact<'a, O> for PrismFact<O, T, F>
 where
-    T: Bounds,
-    O: Bounds,
-    F: Fact<T>,
+    T: Bounds<'a>,
+    O: Bounds<'a>,
+    F: Fact<'a, T>,
 {
     #[tracing::instrument(skip(self))]
     fn check(&self, o: &O) -> Check {
@@ -125,17 +115,18 @@ where
             if let Some(t) = (self.prism)(&mut *o) {
                 self.inner_fact
                     .check(t)
-                    .map(|err| format!("prism({}) > {}", self.label, err))
+                    .prefix_path(format!("prism({})", self.label))
+                    .push_context(self.label.clone())
             } else {
-                Vec::with_capacity(0).into()
+                Vec::<CheckTree>::with_capacity(0).into()
             }
         }
     }
 
-    #[tracing::instrument(skip(self, u))]
-    fn mutate(&self, obj: &mut O, u: &mut Unstructured<'static>) {
+    #[tracing::instrument(skip(self, u, m))]
+    fn mutate(&mut self, obj: &mut O, u: &mut Unstructured<'a>, m: &mut Mutation) {
         if let Some(t) = (self.prism)(obj) {
-            self.inner_fact.mutate(t, u)
+            self.inner_fact.mutate(t, u, m)
         }
     }
 
@@ -152,6 +143,25 @@ where
             }
         }
     }
+
+    fn describe(&self) -> FactNode {
+        FactNode {
+            label: format!("prism({})", self.label),
+            children: vec![self.inner_fact.describe()],
+        }
+    }
+
+    fn is_stateful(&self) -> bool {
+        self.inner_fact.is_stateful()
+    }
+}
+
+impl<'a, O, T, F> StatelessFact<'a, O> for PrismFact<O, T, F>
+where
+    T: Bounds<'a>,
+    O: Bounds<'a>,
+    F: StatelessFact<'a, T>,
+{
 }
 
 #[cfg(test)]
@@ -193,7 +203,7 @@ mod tests {
             ]
         };
 
-        let seq = build_seq(&mut u, 6, f());
+        let seq = build_seq(&mut u, 6, f()).unwrap();
         check_seq(seq.as_slice(), f()).unwrap();
 
         assert!(seq.iter().all(|e| match e {
@@ -223,7 +233,7 @@ mod tests {
             ]
         };
 
-        let seq = build_seq(&mut u, 10, f());
+        let seq = build_seq(&mut u, 10, f()).unwrap();
         check_seq(seq.as_slice(), f()).unwrap();
 
         // Assert that each variant of E is independently increasing
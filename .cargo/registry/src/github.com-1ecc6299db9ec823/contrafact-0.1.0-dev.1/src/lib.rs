@@ -26,7 +26,7 @@
 //! assert!(fact.check(&S {x: 2, y: 333}).is_err());
 //!
 //! let mut u = Unstructured::new(&[0; 9999]);  // NB: don't actually construct Unstructured this way!
-//! let a = fact.build(&mut u);
+//! let a = fact.build(&mut u).unwrap();
 //! assert_eq!(a.x, 1);
 //! ```
 //!
@@ -60,20 +60,30 @@
 mod check;
 mod fact;
 mod impls;
+mod mutation;
 mod satisfy;
+mod shrink;
+mod trace;
 
 pub use arbitrary;
 
-pub use check::Check;
-pub use fact::{BoxFact, Fact, Facts};
+pub use check::{Check, CheckTree, Failure, Segment, Severity};
+pub use fact::{
+    to_dot, BoxFact, BoxStatelessFact, Fact, FactNode, Facts, FactsRef, StatelessFact,
+    StatelessFacts,
+};
+pub use mutation::Mutation;
 pub use satisfy::*;
+pub use shrink::{check_shrunk, shrink};
+pub use trace::{build_trace, minimize_trace, Trace, TraceFailure, TraceStep};
 
 pub use impls::primitives::{
-    always, consecutive_int, consecutive_int_, eq, eq_, in_iter, in_iter_, ne, ne_, never, not,
-    not_, or,
+    always, consecutive_int, consecutive_int_, eq, eq_, ge, ge_, gt, gt_, in_iter, in_iter_, le,
+    le_, lt, lt_, ne, ne_, never, not, not_, or, warn, WarnFact,
 };
 
-pub use impls::brute::{brute, brute_fallible, BruteFact};
+pub use impls::brute::{brute, brute_fallible, BruteFact, BruteStrategy};
+pub use impls::deref::deref;
 pub use impls::lens::{lens, LensFact};
 pub use impls::mapped::{mapped, mapped_fallible, MappedFact};
 pub use impls::prism::{prism, PrismFact};
@@ -0,0 +1,44 @@
+/// Accumulates the outcome of a single `Fact::mutate` call: whether the
+/// value was actually written to, and any errors encountered along the way.
+///
+/// Unlike [`Check`](crate::Check), a `Mutation` is written into rather than
+/// returned, so that combinators (`facts!`, `or`, `not`, ...) can thread a
+/// single accumulator through a chain of mutations without requiring the
+/// subject type to be `Clone`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mutation {
+    errors: Vec<String>,
+    changed: bool,
+}
+
+impl Mutation {
+    /// Create a fresh `Mutation`, recording no changes and no errors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that the value being mutated was actually written to.
+    pub fn mark_changed(&mut self) {
+        self.changed = true;
+    }
+
+    /// Record an error encountered while mutating.
+    pub fn error<S: ToString>(&mut self, err: S) {
+        self.errors.push(err.to_string());
+    }
+
+    /// Whether this mutation actually altered the value.
+    pub fn has_changed(&self) -> bool {
+        self.changed
+    }
+
+    /// Whether any errors were recorded.
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// The errors recorded so far.
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+}
@@ -1,45 +1,169 @@
-use crate::{fact::Bounds, Check, Fact};
+use crate::{fact::Bounds, Check, CheckTree, Fact};
 use arbitrary::Unstructured;
+use rayon::prelude::*;
 
 /// Check that all of the constraints of all Facts are satisfied for this sequence.
 /// Each Fact will run [`Fact::advance`] after each item checked, allowing stateful
 /// facts to change as the sequence advances.
+///
+/// Each item's failures are prefixed with `"item {i}"`, preserving whatever
+/// [`Severity`](crate::Severity) the underlying fact assigned them: an item
+/// that only produces warnings won't cause the overall `Check` to report
+/// failure. A `Fatal`-severity failure (see [`Check::is_fatal`]) stops the
+/// walk immediately, without advancing past or checking later items.
 #[tracing::instrument(skip(fact))]
-pub fn check_seq<T, F>(seq: &[T], mut fact: F) -> Check
+pub fn check_seq<'a, T, F>(seq: &[T], mut fact: F) -> Check
 where
-    F: Fact<T>,
-    T: Bounds,
+    F: Fact<'a, T>,
+    T: Bounds<'a>,
 {
-    let mut reasons: Vec<String> = Vec::new();
+    let mut errors: Vec<CheckTree> = Vec::new();
     for (i, obj) in seq.iter().enumerate() {
-        reasons.extend(
+        let check = fact
+            .check(obj)
+            .prefix_path(format!("item {}", i))
+            .push_context(i);
+        let fatal = check.is_fatal();
+        errors.extend(check);
+        if fatal {
+            break;
+        }
+        fact.advance(obj);
+    }
+    errors.into()
+}
+
+/// Like [`check_seq`], but splits the sequence across a rayon thread pool and
+/// checks chunks in parallel, merging the per-item failures back in original
+/// order. Useful for large sequences (e.g. DHT-op-like fixtures) where a
+/// fully serial walk is the bottleneck.
+///
+/// Since [`Fact`] requires `Send + Sync`, any fact can in principle be handed
+/// to a thread pool -- the catch is [`Fact::advance`]. A fact whose `advance`
+/// carries state from one item to the next cannot be split into independent
+/// chunks without changing its answers, so this checks
+/// [`fact.is_stateful()`](Fact::is_stateful) first and falls back to the
+/// plain serial [`check_seq`] whenever it reports `true`.
+///
+/// All items are still checked concurrently (there's no way to pre-empt work
+/// already dispatched to the thread pool), but a `Fatal`-severity failure
+/// (see [`Check::is_fatal`]) makes the merged result stop at that item,
+/// discarding any failures from later items, same as the serial [`check_seq`].
+#[tracing::instrument(skip(fact))]
+pub fn check_seq_par<'a, T, F>(seq: &[T], fact: F) -> Check
+where
+    F: Fact<'a, T>,
+    T: Bounds<'a> + Sync,
+{
+    if fact.is_stateful() {
+        return check_seq(seq, fact);
+    }
+
+    let per_item: Vec<Vec<CheckTree>> = seq
+        .par_iter()
+        .enumerate()
+        .map(|(i, obj)| {
             fact.check(obj)
+                .prefix_path(format!("item {}", i))
+                .push_context(i)
                 .into_iter()
-                .map(|reason| format!("item {}: {}", i, reason))
-                .collect::<Vec<_>>(),
-        );
-        fact.advance(obj);
+                .collect()
+        })
+        .collect();
+
+    let mut errors: Vec<CheckTree> = Vec::new();
+    for item in per_item {
+        let check: Check = item.into();
+        let fatal = check.is_fatal();
+        errors.extend(check);
+        if fatal {
+            break;
+        }
+    }
+    errors.into()
+}
+
+/// Given a sequence known to fail [`check_seq`], find a smaller failing
+/// sub-sequence via delta-debugging (ddmin): starting from granularity
+/// `n = 2`, partition the sequence into `n` contiguous chunks and test each
+/// chunk's *complement* (the sequence with that chunk removed). If a
+/// complement still fails, it becomes the new sequence and `n` is lowered
+/// back towards `2`; if none do, `n` is doubled to look at finer-grained
+/// chunks. Terminates once `n` can no longer be increased without exceeding
+/// the sequence length, returning whatever is left.
+///
+/// Facts here are stateful -- [`Fact::advance`] runs once per item -- so
+/// every trial re-runs [`check_seq`] from a fresh
+/// [`clone`](Clone::clone) of the original fact rather than reusing one that
+/// has already stepped through part of the sequence, and chunk removal
+/// always preserves the surviving items' original order so their `advance`
+/// history stays meaningful.
+///
+/// Note that a non-deterministic fact (e.g. one built from
+/// [`brute`](crate::brute), which resamples randomly) may make minimization
+/// unstable: a trial that fails once isn't guaranteed to fail again.
+pub fn minimize_seq<'a, T, F>(seq: &[T], fact: F) -> Vec<T>
+where
+    T: Bounds<'a> + Clone,
+    F: Fact<'a, T> + Clone,
+{
+    let mut current: Vec<T> = seq.to_vec();
+    let mut n = 2usize;
+
+    while current.len() >= 2 {
+        let chunk_size = (current.len() + n - 1) / n;
+        let mut shrunk = false;
+
+        for i in 0..n {
+            let start = i * chunk_size;
+            if start >= current.len() {
+                break;
+            }
+            let end = (start + chunk_size).min(current.len());
+
+            let mut complement = current[..start].to_vec();
+            complement.extend_from_slice(&current[end..]);
+
+            if check_seq(complement.as_slice(), fact.clone()).is_err() {
+                current = complement;
+                n = (n - 1).max(2);
+                shrunk = true;
+                break;
+            }
+        }
+
+        if !shrunk {
+            if n >= current.len() {
+                break;
+            }
+            n = (2 * n).min(current.len());
+        }
     }
-    reasons.into()
+
+    current
 }
 
 /// Build a sequence from scratch such that all Facts are satisfied.
 /// Each Fact will run [`Fact::advance`] after each item built, allowing stateful
 /// facts to change as the sequence advances.
 #[tracing::instrument(skip(u, fact))]
-pub fn build_seq<T, F>(u: &mut Unstructured<'static>, num: usize, mut fact: F) -> Vec<T>
+pub fn build_seq<'a, T, F>(
+    u: &mut Unstructured<'a>,
+    num: usize,
+    mut fact: F,
+) -> crate::Result<Vec<T>>
 where
-    T: Bounds,
-    F: Fact<T>,
+    T: Bounds<'a>,
+    F: Fact<'a, T>,
 {
     let mut seq = Vec::new();
     for _i in 0..num {
         tracing::trace!("i: {}", _i);
-        let obj = fact.build(u);
+        let obj = fact.build(u)?;
         fact.advance(&obj);
         seq.push(obj);
     }
-    return seq;
+    Ok(seq)
 }
 
 /// Convenience macro for creating a collection of [`Fact`](crate::Fact)s
@@ -66,3 +190,27 @@ macro_rules! facts {
         fs
     }};
 }
+
+/// Same as [`facts!`], but for collecting [`StatelessFact`](crate::StatelessFact)s
+/// into a [`StatelessFacts`](crate::StatelessFacts). Required wherever facts
+/// are assembled on-the-fly and so cannot carry state across items, e.g. the
+/// closure passed to [`mapped`](crate::mapped)/[`mapped_fallible`](crate::mapped_fallible).
+///
+/// ```
+/// use contrafact::*;
+///
+/// let eq1 = eq_(1);
+/// let not2 = not_(eq_(2));
+/// let fact: StatelessFacts<'static, u32> = stateless_facts![eq1, not2];
+/// assert!(fact.check(&1).is_ok());
+/// ```
+#[macro_export]
+macro_rules! stateless_facts {
+    ( $( $fact:expr ),+ $(,)?) => {{
+        let mut fs: $crate::StatelessFacts<_> = Vec::new();
+        $(
+            fs.push(Box::new($fact));
+        )+
+        fs
+    }};
+}
@@ -0,0 +1,312 @@
+use crate::{fact::Bounds, Check, Fact};
+use arbitrary::Unstructured;
+
+/// One generated step of a [`Trace`]: the value itself, plus the entropy
+/// buffer it was built from. The buffer is kept around so
+/// [`minimize_trace`] can reshrink a single step in isolation instead of
+/// having to regenerate the whole trace from scratch.
+#[derive(Debug, Clone)]
+pub struct TraceStep<T> {
+    /// The value generated for this step.
+    pub value: T,
+    seed: Vec<u8>,
+}
+
+/// The step index, failing value, and [`Check`] at the first [`Trace`] step
+/// that fails [`Fact::check`].
+#[derive(Debug, Clone)]
+pub struct TraceFailure<T> {
+    /// The index of the first failing step.
+    pub index: usize,
+    /// The value at that step.
+    pub value: T,
+    /// Why it failed.
+    pub check: Check,
+}
+
+/// An ordered sequence of generated values together with the stateful
+/// [`Fact`] they're checked against, modeling a sequence of actions against
+/// a changing fact (e.g. `join_game_with_code` followed by
+/// `get_player_profile_for_game_code`) rather than a single value.
+///
+/// Unlike [`check_seq`](crate::check_seq), which keeps walking a sequence to
+/// report every item's failures, running a `Trace` stops at (and only
+/// reports) the *first* failing step: once a stateful fact's invariant has
+/// broken, its `advance` may no longer describe a meaningful next state.
+#[derive(Clone)]
+pub struct Trace<T, F> {
+    steps: Vec<TraceStep<T>>,
+    fact: F,
+}
+
+impl<T, F> Trace<T, F> {
+    /// Construct a trace from an explicit list of steps and the fact to
+    /// check them against.
+    pub fn new(steps: Vec<TraceStep<T>>, fact: F) -> Self {
+        Self { steps, fact }
+    }
+
+    /// The generated values, in order.
+    pub fn values(&self) -> Vec<&T> {
+        self.steps.iter().map(|step| &step.value).collect()
+    }
+
+    /// The number of steps in this trace.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Whether this trace has no steps.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Run this trace against a fresh clone of its fact, checking each step
+    /// in turn and calling [`Fact::advance`] between them. Returns the
+    /// first step that fails, or `None` if every step passed.
+    pub fn run(&self) -> Option<TraceFailure<T>>
+    where
+        T: Clone,
+        for<'x> T: Bounds<'x>,
+        for<'x> F: Fact<'x, T> + Clone,
+    {
+        let mut fact = self.fact.clone();
+        for (index, step) in self.steps.iter().enumerate() {
+            let check = fact.check(&step.value);
+            if check.is_err() {
+                return Some(TraceFailure {
+                    index,
+                    value: step.value.clone(),
+                    check,
+                });
+            }
+            fact.advance(&step.value);
+        }
+        None
+    }
+}
+
+/// Build a [`Trace`] from `u`: chooses a length up to `max_len`, then builds
+/// each step from its own slice of `u`'s remaining entropy (kept around so
+/// [`minimize_trace`] can reshrink a step in isolation later), calling
+/// [`Fact::advance`] between steps the same way [`build_seq`](crate::build_seq)
+/// does.
+///
+/// Each step's entropy is copied out of `u` and built from its own
+/// locally-owned `Unstructured`, so `T`/`F` need to satisfy their bounds for
+/// *any* lifetime, not just `u`'s -- the same `for<'x>` pattern
+/// [`shrink`](crate::shrink) and [`check_shrunk`](crate::check_shrunk) use,
+/// and for the same reason.
+pub fn build_trace<'a, T, F>(
+    u: &mut Unstructured<'a>,
+    max_len: usize,
+    fact: F,
+) -> crate::Result<Trace<T, F>>
+where
+    for<'x> T: Bounds<'x>,
+    for<'x> F: Fact<'x, T> + Clone,
+{
+    let original = fact.clone();
+    let mut fact = fact;
+    let len = u.int_in_range(0..=max_len)?;
+
+    let mut steps = Vec::with_capacity(len);
+    for _ in 0..len {
+        let remaining = u.len();
+        let take = if remaining == 0 {
+            0
+        } else {
+            u.int_in_range(1..=remaining)?
+        };
+        let seed = u.bytes(take)?.to_vec();
+
+        let mut step_u = Unstructured::new(&seed);
+        let value = fact.build(&mut step_u)?;
+        fact.advance(&value);
+        steps.push(TraceStep { value, seed });
+    }
+
+    Ok(Trace::new(steps, original))
+}
+
+/// Rebuild a single step's value from `seed`, given `fact` advanced through
+/// `history` (the steps preceding it in the trace), so the rebuilt step sees
+/// the same state a real run of the trace would have produced.
+fn rebuild_step<T, F>(fact: &F, history: &[TraceStep<T>], seed: &[u8]) -> Option<TraceStep<T>>
+where
+    T: Clone,
+    for<'x> T: Bounds<'x>,
+    for<'x> F: Fact<'x, T> + Clone,
+{
+    let mut fact = fact.clone();
+    for step in history {
+        fact.advance(&step.value);
+    }
+    let mut u = Unstructured::new(seed);
+    let value = fact.build(&mut u).ok()?;
+    Some(TraceStep {
+        value,
+        seed: seed.to_vec(),
+    })
+}
+
+/// Given a [`Trace`] known to fail via [`Trace::run`], find a smaller trace
+/// that still fails: first remove steps that aren't needed to reproduce the
+/// failure, via the same delta-debugging (ddmin) approach as
+/// [`minimize_seq`](crate::minimize_seq), then shrink each surviving step's
+/// entropy buffer the same way, rebuilding that step's value from the
+/// smaller buffer via [`Fact::build`]. Every candidate reduction re-runs the
+/// whole trace via [`Trace::run`] and is only kept if it still fails.
+pub fn minimize_trace<T, F>(trace: Trace<T, F>) -> Trace<T, F>
+where
+    T: Clone,
+    for<'x> T: Bounds<'x>,
+    for<'x> F: Fact<'x, T> + Clone,
+{
+    let mut current = trace;
+
+    // (a) remove steps that aren't needed to reproduce the failure.
+    let mut n = 2usize;
+    while current.steps.len() >= 2 {
+        let len = current.steps.len();
+        let chunk_size = (len + n - 1) / n;
+        let mut shrunk = false;
+
+        for i in 0..n {
+            let start = i * chunk_size;
+            if start >= len {
+                break;
+            }
+            let end = (start + chunk_size).min(len);
+
+            let mut candidate_steps = current.steps[..start].to_vec();
+            candidate_steps.extend_from_slice(&current.steps[end..]);
+            let candidate = Trace::new(candidate_steps, current.fact.clone());
+            if candidate.run().is_some() {
+                current = candidate;
+                n = 2;
+                shrunk = true;
+                break;
+            }
+        }
+
+        if !shrunk {
+            if n >= current.steps.len() {
+                break;
+            }
+            n = (2 * n).min(current.steps.len());
+        }
+    }
+
+    // (b) shrink each surviving step's entropy buffer.
+    for i in 0..current.steps.len() {
+        let history = current.steps[..i].to_vec();
+        let mut buf = current.steps[i].seed.clone();
+        let mut n = 2usize;
+
+        while buf.len() >= 2 {
+            let chunk_size = (buf.len() + n - 1) / n;
+            let mut shrunk = false;
+
+            for c in 0..n {
+                let start = c * chunk_size;
+                if start >= buf.len() {
+                    break;
+                }
+                let end = (start + chunk_size).min(buf.len());
+
+                let chunk = buf[start..end].to_vec();
+                if let Some(candidate_step) = rebuild_step(&current.fact, &history, &chunk) {
+                    let mut candidate = current.clone();
+                    candidate.steps[i] = candidate_step;
+                    if candidate.run().is_some() {
+                        current = candidate;
+                        buf = chunk;
+                        n = 2;
+                        shrunk = true;
+                        break;
+                    }
+                }
+
+                let mut complement = buf[..start].to_vec();
+                complement.extend_from_slice(&buf[end..]);
+                if let Some(candidate_step) = rebuild_step(&current.fact, &history, &complement) {
+                    let mut candidate = current.clone();
+                    candidate.steps[i] = candidate_step;
+                    if candidate.run().is_some() {
+                        current = candidate;
+                        buf = complement;
+                        n = 2;
+                        shrunk = true;
+                        break;
+                    }
+                }
+            }
+
+            if !shrunk {
+                if n >= buf.len() {
+                    break;
+                }
+                n = (2 * n).min(buf.len());
+            }
+        }
+    }
+
+    current
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Mutation;
+
+    #[derive(Debug, Clone, PartialEq, arbitrary::Arbitrary)]
+    struct Counter(u8);
+
+    #[derive(Clone)]
+    struct MustStayBelow(u8);
+
+    impl<'a> Fact<'a, Counter> for MustStayBelow {
+        fn check(&self, obj: &Counter) -> Check {
+            Check::check(obj.0 < self.0, "counter must stay below threshold")
+        }
+
+        fn mutate(&mut self, _: &mut Counter, _: &mut Unstructured<'a>, _: &mut Mutation) {
+            unimplemented!()
+        }
+
+        fn advance(&mut self, _: &Counter) {}
+    }
+
+    fn step(value: u8) -> TraceStep<Counter> {
+        TraceStep {
+            value: Counter(value),
+            seed: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_run_finds_first_failure() {
+        let trace = Trace::new(vec![step(1), step(2), step(10), step(3)], MustStayBelow(5));
+        let failure = trace.run().unwrap();
+        assert_eq!(failure.index, 2);
+        assert_eq!(failure.value, Counter(10));
+    }
+
+    #[test]
+    fn test_run_passes_when_all_steps_satisfy() {
+        let trace = Trace::new(vec![step(1), step(2), step(3)], MustStayBelow(5));
+        assert!(trace.run().is_none());
+    }
+
+    #[test]
+    fn test_minimize_trace_removes_unneeded_steps() {
+        let trace = Trace::new(
+            vec![step(1), step(2), step(3), step(10), step(1)],
+            MustStayBelow(5),
+        );
+        let minimized = minimize_trace(trace);
+        assert!(minimized.run().is_some());
+        assert!(minimized.len() < 5);
+    }
+}
@@ -70,7 +70,7 @@ fn test_link() {
     let author = "alice".to_string();
     let fact = || chain_fact(&author);
 
-    let mut chain = build_seq(&mut u, NUM as usize, fact());
+    let mut chain = build_seq(&mut u, NUM as usize, fact()).unwrap();
     dbg!(&chain);
     check_seq(chain.as_mut_slice(), fact()).unwrap();
 
@@ -87,7 +87,7 @@ fn test_wrapper() {
     let author = "alice".to_string();
     let fact = || wrapper_fact(&author, &[Color::Cyan, Color::Magenta]);
 
-    let mut chain = build_seq(&mut u, NUM as usize, fact());
+    let mut chain = build_seq(&mut u, NUM as usize, fact()).unwrap();
     dbg!(&chain);
     check_seq(chain.as_mut_slice(), fact()).unwrap();
 
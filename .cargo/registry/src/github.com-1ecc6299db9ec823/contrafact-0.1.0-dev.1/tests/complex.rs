@@ -117,7 +117,7 @@ fn test_omega_fact() {
     let mut u = Unstructured::new(&NOISE);
 
     let data = "spartacus".into();
-    let fact = omega_fact(&11, &data);
+    let mut fact = omega_fact(&11, &data);
 
     let beta = Beta::arbitrary(&mut u).unwrap();
 
@@ -138,10 +138,10 @@ fn test_omega_fact() {
         beta: beta.clone(),
     };
 
-    fact.mutate(&mut valid1, &mut u);
+    fact.mutate(&mut valid1, &mut u, &mut Mutation::new());
     fact.check(dbg!(&valid1)).unwrap();
 
-    fact.mutate(&mut valid2, &mut u);
+    fact.mutate(&mut valid2, &mut u, &mut Mutation::new());
     fact.check(dbg!(&valid2)).unwrap();
 
     let mut invalid1 = Omega::Alpha {
@@ -167,7 +167,7 @@ fn test_omega_fact() {
         dbg!(fact.check(dbg!(&invalid1)).result().unwrap_err()).len(),
         4,
     );
-    fact.mutate(&mut invalid1, &mut u);
+    fact.mutate(&mut invalid1, &mut u, &mut Mutation::new());
     fact.check(dbg!(&invalid1)).unwrap();
 
     // Ensure that check fails for invalid data
@@ -175,6 +175,6 @@ fn test_omega_fact() {
         dbg!(fact.check(dbg!(&invalid2)).result().unwrap_err()).len(),
         5,
     );
-    fact.mutate(&mut invalid2, &mut u);
+    fact.mutate(&mut invalid2, &mut u, &mut Mutation::new());
     fact.check(dbg!(&invalid2)).unwrap();
 }
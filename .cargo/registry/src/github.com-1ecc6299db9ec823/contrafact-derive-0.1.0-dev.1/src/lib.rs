@@ -0,0 +1,201 @@
+//! `#[derive(Fact)]`: generate a `contrafact::Facts<'static, Self>` out of
+//! per-field `#[fact(...)]` attributes, so that types whose invariants are
+//! just a conjunction of per-field constraints don't need to be wired up by
+//! hand with `lens`/`prism`/`facts!`.
+//!
+//! ```ignore
+//! use contrafact::*;
+//! use contrafact_derive::Fact;
+//!
+//! #[derive(Debug, Clone, PartialEq, Arbitrary, Fact)]
+//! struct S {
+//!     #[fact(eq(&1))]
+//!     x: u32,
+//!     #[fact(in_iter(&ALLOWED))]
+//!     y: u32,
+//! }
+//!
+//! static ALLOWED: [u32; 3] = [2, 3, 4];
+//!
+//! let mut fact = S::facts();
+//! assert!(fact.check(&S { x: 1, y: 3 }).is_ok());
+//! assert!(fact.check(&S { x: 2, y: 3 }).is_err());
+//! ```
+//!
+//! A field can also be annotated `#[fact(nested)]` instead of a combinator
+//! call, to recurse into a field whose own type derives `Fact`: the field's
+//! contribution becomes `<FieldType>::facts()` lifted through a `lens`
+//! (or a `prism`, for an enum variant's field) rather than a primitive.
+//!
+//! For an enum, each annotated field is lifted with a `prism` that matches
+//! on the owning variant, so the fact only applies when `self` is actually
+//! that variant -- exactly the hand-written pattern `contrafact::prism`'s
+//! own doc examples use.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Expr, Fields, Ident, Index};
+use synstructure::{decl_derive, Structure};
+
+decl_derive!([Fact, attributes(fact)] => derive_fact);
+
+/// What a single `#[fact(...)]` attribute asks the derive to contribute for
+/// its field.
+enum FieldFact {
+    /// `#[fact(nested)]`
+    Nested,
+    /// `#[fact(some_combinator(args...))]`
+    Call(Expr),
+}
+
+fn field_fact(attrs: &[syn::Attribute]) -> Option<FieldFact> {
+    let attr = attrs.iter().find(|a| a.path.is_ident("fact"))?;
+    let inner: Expr = attr.parse_args().unwrap_or_else(|e| {
+        panic!(
+            "#[fact(...)] must contain a single expression, e.g. `#[fact(eq(&1))]` or `#[fact(nested)]`: {}",
+            e
+        )
+    });
+    if let Expr::Path(p) = &inner {
+        if p.path.is_ident("nested") {
+            return Some(FieldFact::Nested);
+        }
+    }
+    Some(FieldFact::Call(inner))
+}
+
+/// Build the inner fact expression for one annotated field, given its label.
+fn inner_fact_expr(fact: FieldFact, label: &str, field_ty: &syn::Type) -> TokenStream {
+    match fact {
+        // The nested type is expected to derive `Fact` itself, which gives
+        // it the same inherent `facts()` constructor we're generating here.
+        FieldFact::Nested => quote! {
+            <#field_ty>::facts()
+        },
+        FieldFact::Call(Expr::Call(call)) => {
+            let func = &call.func;
+            let args = call.args.iter();
+            quote! { #func(#label, #(#args),*) }
+        }
+        // A bare path like `always` (no parens) is also accepted, mirroring
+        // how contrafact's own zero-arg facts (e.g. `always()`) are called.
+        FieldFact::Call(other) => quote! { #other },
+    }
+}
+
+fn derive_fact(mut s: Structure) -> TokenStream {
+    s.bind_with(|_| synstructure::BindStyle::RefMut);
+
+    let self_name = s.ast().ident.to_string();
+    let is_enum = matches!(s.ast().data, syn::Data::Enum(_));
+
+    let mut contributions: Vec<TokenStream> = Vec::new();
+    let mut arbitrary_assertions: Vec<TokenStream> = Vec::new();
+
+    for variant in s.variants() {
+        let variant_ident = variant.ast().ident.clone();
+        for (field_index, field) in variant.ast().fields.iter().enumerate() {
+            let fact = match field_fact(&field.attrs) {
+                Some(fact) => fact,
+                None => continue,
+            };
+
+            // Every annotated field needs to be buildable by `Fact::build`,
+            // which bottoms out in `arbitrary::Arbitrary`.
+            let field_ty = &field.ty;
+            arbitrary_assertions.push(quote! {
+                __assert_arbitrary::<#field_ty>();
+            });
+
+            let (field_label, access) = match &field.ident {
+                Some(ident) => (
+                    format!("{}::{}", self_name, ident),
+                    quote! { #ident },
+                ),
+                None => {
+                    let idx = Index::from(field_index);
+                    (format!("{}::{}", self_name, field_index), quote! { #idx })
+                }
+            };
+
+            let inner = inner_fact_expr(fact, &field_label, &field.ty);
+
+            let contribution = if is_enum {
+                let pattern = enum_field_pattern(&variant_ident, &variant.ast().fields, field_index);
+                quote! {
+                    contrafact::prism(
+                        #field_label,
+                        |__fact_self: &mut Self| match __fact_self {
+                            #pattern => Some(__fact_binding),
+                            _ => None,
+                        },
+                        #inner,
+                    )
+                }
+            } else {
+                quote! {
+                    contrafact::lens(
+                        #field_label,
+                        |__fact_self: &mut Self| &mut __fact_self.#access,
+                        #inner,
+                    )
+                }
+            };
+
+            contributions.push(contribution);
+        }
+    }
+
+    // `facts()` is an inherent method, not a trait impl, so `Structure::gen_impl`
+    // doesn't apply here -- its template grammar requires `gen impl <Trait> for
+    // @Self { ... }`. Build the impl block by hand instead, from the type's own
+    // (unmodified) generics.
+    let ast = s.ast();
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Facts generated from this type's `#[fact(...)]` field attributes.
+            pub fn facts() -> contrafact::Facts<'static, Self> {
+                fn __assert_arbitrary<T>()
+                where
+                    T: for<'a> contrafact::arbitrary::Arbitrary<'a>,
+                {
+                }
+                #(#arbitrary_assertions)*
+                contrafact::facts![ #(#contributions),* ]
+            }
+        }
+    }
+}
+
+/// Build a `Self::Variant { field: ref mut __fact_binding, .. }`-style
+/// pattern (or the tuple-variant equivalent) that binds only the target
+/// field, for use in a prism's extractor closure.
+fn enum_field_pattern(variant_ident: &Ident, fields: &Fields, target: usize) -> TokenStream {
+    match fields {
+        Fields::Named(named) => {
+            let binders = named.named.iter().enumerate().map(|(i, f)| {
+                let ident = f.ident.as_ref().expect("named field");
+                if i == target {
+                    quote! { #ident: ref mut __fact_binding }
+                } else {
+                    quote! { #ident: _ }
+                }
+            });
+            quote! { Self::#variant_ident { #(#binders),* } }
+        }
+        Fields::Unnamed(unnamed) => {
+            let binders = (0..unnamed.unnamed.len()).map(|i| {
+                if i == target {
+                    quote! { ref mut __fact_binding }
+                } else {
+                    quote! { _ }
+                }
+            });
+            quote! { Self::#variant_ident(#(#binders),*) }
+        }
+        Fields::Unit => unreachable!("a unit variant has no fields to annotate"),
+    }
+}
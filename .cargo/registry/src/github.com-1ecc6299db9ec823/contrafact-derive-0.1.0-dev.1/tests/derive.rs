@@ -0,0 +1,29 @@
+use arbitrary::{Arbitrary, Unstructured};
+use contrafact::*;
+use contrafact_derive::Fact;
+
+#[derive(Debug, Clone, PartialEq, Arbitrary, Fact)]
+struct S {
+    #[fact(eq(&1))]
+    x: u32,
+    #[fact(in_iter(&ALLOWED))]
+    y: u32,
+}
+
+static ALLOWED: [u32; 3] = [2, 3, 4];
+
+#[test]
+fn test_facts_check() {
+    let mut fact = S::facts();
+    assert!(fact.check(&S { x: 1, y: 3 }).is_ok());
+    assert!(fact.check(&S { x: 2, y: 3 }).is_err());
+    assert!(fact.check(&S { x: 1, y: 99 }).is_err());
+}
+
+#[test]
+fn test_facts_build_satisfies_itself() {
+    let mut u = Unstructured::new(&[0; 9999]);
+    let mut fact = S::facts();
+    let s = fact.build(&mut u).unwrap();
+    assert!(S::facts().check(&s).is_ok());
+}
@@ -15,10 +15,21 @@ type Fields = Vec<syn::Field>;
 
 bitflags! {
   /// Controls which code and implementations we generate.
+  ///
+  /// `SW_AS_REF` and `SW_BORROW` are recognized for parity with `SW_INDEX`
+  /// below, but are currently no-ops: `AsRef`/`Borrow` (and, under
+  /// `SW_MUT`, `AsMut`/`BorrowMut`) are already derived unconditionally by
+  /// `impl_immut_borrows`/`impl_mut_borrows`, so there's nothing left for
+  /// these flags to gate. They exist so that `#[shrinkwrap(as_ref)]`/
+  /// `#[shrinkwrap(borrow)]` are recognized flags; setting them does not
+  /// change the generated code.
   pub struct ShrinkwrapFlags: u32 {
     const SW_MUT          = 0b00000001;
     const SW_IGNORE_VIS   = 0b00000010;
     const SW_TRANSFORMERS = 0b00000100;
+    const SW_AS_REF       = 0b00001000;
+    const SW_BORROW       = 0b00010000;
+    const SW_INDEX        = 0b00100000;
   }
 }
 
@@ -35,16 +46,73 @@ pub struct Struct {
   pub inner_field_name: proc_macro2::TokenStream
 }
 
+/// How a single enum variant reaches the field it shares with every other
+/// variant, so that `EnumCommon`'s `match self { .. }` arms can be built
+/// without needing to re-inspect the original `syn::Fields` at codegen time.
+pub enum EnumVariantKind {
+  /// An unnamed (tuple) variant; `index` is the position of the shared field
+  /// among the variant's `arity` total fields.
+  Tuple { arity: usize, index: usize },
+  /// A named (struct-style) variant; every variant must agree on this name.
+  Named { field_name: syn::Ident },
+}
+
+/// One variant of an enum whose variants all share a field of the same type,
+/// reachable in a `match self { .. }`.
+pub struct EnumVariantField {
+  pub variant_ident: syn::Ident,
+  pub kind: EnumVariantKind,
+}
+
+impl EnumVariantField {
+  /// Builds the match arm pattern for this variant, binding the shared field
+  /// to `__shrinkwrap_inner` (by `ref` or `ref mut`) and `_`-ing out every
+  /// other field.
+  pub fn pattern(&self, enum_ident: &syn::Ident, mutable: bool) -> proc_macro2::TokenStream {
+    let variant_ident = &self.variant_ident;
+    let binding = if mutable {
+      quote!(ref mut __shrinkwrap_inner)
+    } else {
+      quote!(ref __shrinkwrap_inner)
+    };
+
+    match self.kind {
+      EnumVariantKind::Tuple { arity, index } => {
+        let elems = (0..arity).map(|i| {
+          if i == index { binding.clone() } else { quote!(_) }
+        });
+        quote!( #enum_ident::#variant_ident( #(#elems),* ) )
+      },
+      EnumVariantKind::Named { ref field_name } => {
+        quote!( #enum_ident::#variant_ident { #field_name: #binding, .. } )
+      },
+    }
+  }
+}
+
+/// Represents an enum all of whose variants carry a field of the same type,
+/// dispatched to via `match self { .. }` rather than via a single field name.
+pub struct EnumCommon {
+  pub inner_type: syn::Type,
+  pub variants: Vec<EnumVariantField>,
+}
+
+/// Either of the two shapes Shrinkwrap knows how to generate code for.
+pub enum ShrinkwrapInput {
+  Struct(Struct),
+  Enum(EnumCommon),
+}
+
 /// Check if the input stream matches our required data structures.
 /// The TokenStream on error contains a compile error pointing to the right place.
 pub fn validate_derive_input(input: syn::DeriveInput)
-  -> Result<(StructDetails, Struct), TokenStream>
+  -> Result<(StructDetails, ShrinkwrapInput), TokenStream>
 {
   // We *don't* want to use `panic` and `unwrap` here, even though they're
   // safe, because we want our compile errors to be attached to the right
   // lines of code.
 
-  use syn::{DeriveInput, DataStruct, FieldsUnnamed, FieldsNamed};
+  use syn::{DeriveInput, DataStruct, DataEnum, FieldsUnnamed, FieldsNamed};
   use syn::Data::{Struct, Enum, Union};
   use syn::Fields::{Named, Unnamed};
 
@@ -54,24 +122,148 @@ pub fn validate_derive_input(input: syn::DeriveInput)
   let flags = shrinkwrap_flags(&attrs);
   let details = StructDetails { flags, ident, visibility: vis, generics };
 
-  let strct = match data {
+  let input = match data {
     Struct(DataStruct { fields: Unnamed(FieldsUnnamed { unnamed: fields, .. }), .. }) => {
       let fields = fields.into_iter().collect_vec();
-      validate_tuple(whole_span, fields)
+      validate_tuple(whole_span, fields).map(ShrinkwrapInput::Struct)
     },
     Struct(DataStruct { fields: Named(FieldsNamed { named: fields, .. }), .. }) => {
       let fields = fields.into_iter().collect_vec();
-      validate_nontuple(whole_span, fields)
+      validate_nontuple(whole_span, fields).map(ShrinkwrapInput::Struct)
     },
     Struct(..) =>
       Err(compile_error_at(whole_span, "Shrinkwrap needs a struct with at least one field!")),
-    Enum(..) =>
-      Err(compile_error_at(whole_span, "Shrinkwrap does not support enums!")),
+    Enum(DataEnum { variants, .. }) => {
+      let variants = variants.into_iter().collect_vec();
+      validate_enum(whole_span, variants).map(ShrinkwrapInput::Enum)
+    },
     Union(..) =>
       Err(compile_error_at(whole_span, "Shrinkwrap does not support C-style unions!"))
   }?;
 
-  Ok((details, strct))
+  Ok((details, input))
+}
+
+fn variant_fields(fields: &syn::Fields) -> Fields {
+  match fields {
+    syn::Fields::Named(named) => named.named.iter().cloned().collect_vec(),
+    syn::Fields::Unnamed(unnamed) => unnamed.unnamed.iter().cloned().collect_vec(),
+    syn::Fields::Unit => vec![],
+  }
+}
+
+/// Validate that every variant of an enum carries a field we can dispatch to
+/// via a shared `Deref`/`DerefMut`: either the variant's sole field, or (when
+/// any variant needs to disambiguate between several fields) the one marked
+/// `#[shrinkwrap(main_field)]`. All variants must agree on the dispatched
+/// field's type, and named variants must additionally agree on its name.
+fn validate_enum(whole_span: Span, variants: Vec<syn::Variant>) -> Result<EnumCommon, TokenStream> {
+  if variants.is_empty() {
+    return Err(compile_error_at(
+      whole_span,
+      "Shrinkwrap requires enums to have at least one variant!"
+    ));
+  }
+
+  let any_marked = variants.iter()
+    .any(|variant| variant_fields(&variant.fields).iter().any(is_marked));
+
+  let mut resolved = Vec::with_capacity(variants.len());
+  let mut common_name: Option<syn::Ident> = None;
+  let mut common_type: Option<(syn::Type, String)> = None;
+
+  for variant in variants {
+    let span = variant.span();
+    let fields = variant_fields(&variant.fields);
+
+    if fields.is_empty() {
+      return Err(compile_error_at(
+        span,
+        &format!(
+          "Shrinkwrap enum variant `{}` has no fields to dispatch to.",
+          variant.ident
+        )
+      ));
+    }
+
+    let index = if any_marked {
+      let marked = fields.iter()
+        .enumerate()
+        .filter(|(_, field)| is_marked(field))
+        .map(|(i, _)| i)
+        .collect_vec();
+
+      match marked.len() {
+        1 => marked[0],
+        0 => return Err(compile_error_at(
+          span,
+          &format!(
+            "Shrinkwrap enum variant `{}` has no field marked with #[shrinkwrap(main_field)], but another variant does. Mark the field to dispatch to in every variant.",
+            variant.ident
+          )
+        )),
+        _ => return Err(compile_error_at(
+          span,
+          &format!(
+            "Shrinkwrap enum variant `{}` has more than one field marked with #[shrinkwrap(main_field)].",
+            variant.ident
+          )
+        )),
+      }
+    } else if fields.len() == 1 {
+      0
+    } else {
+      return Err(compile_error_at(
+        span,
+        &format!(
+          "Shrinkwrap enum variant `{}` has more than one field; mark the one to dispatch to with #[shrinkwrap(main_field)].",
+          variant.ident
+        )
+      ));
+    };
+
+    let field = &fields[index];
+
+    if let Some(ref field_name) = field.ident {
+      match common_name {
+        Some(ref expected) if expected != field_name => return Err(compile_error_at(
+          span,
+          &format!(
+            "Shrinkwrap enum variant `{}` names its shared field `{}`, but an earlier variant names it `{}`. All struct-style variants must share the same field name.",
+            variant.ident, field_name, expected
+          )
+        )),
+        Some(_) => (),
+        None => common_name = Some(field_name.clone()),
+      }
+    }
+
+    let field_ty = &field.ty;
+    let type_string = quote!(#field_ty).to_string();
+    match common_type {
+      Some((_, ref expected_string)) if expected_string != &type_string => return Err(compile_error_at(
+        span,
+        &format!(
+          "Shrinkwrap enum variant `{}`'s shared field has a different type than an earlier variant's. Every variant must share a field of the same type.",
+          variant.ident
+        )
+      )),
+      Some(_) => (),
+      None => common_type = Some((field.ty.clone(), type_string)),
+    }
+
+    let kind = match field.ident {
+      Some(ref field_name) => EnumVariantKind::Named { field_name: field_name.clone() },
+      None => EnumVariantKind::Tuple { arity: fields.len(), index },
+    };
+
+    resolved.push(EnumVariantField { variant_ident: variant.ident, kind });
+  }
+
+  Ok(EnumCommon {
+    inner_type: common_type.unwrap().0,
+    variants: resolved,
+  })
 }
 
 /// Specifically for working with attributes like #[shrinkwrap(..)], where
@@ -109,6 +301,12 @@ fn shrinkwrap_flags(attrs: &[syn::Attribute]) -> ShrinkwrapFlags {
         flags |= ShrinkwrapFlags::SW_IGNORE_VIS;
       } else if path.is_ident("transformers") {
         flags |= ShrinkwrapFlags::SW_TRANSFORMERS;
+      } else if path.is_ident("as_ref") {
+        flags |= ShrinkwrapFlags::SW_AS_REF;
+      } else if path.is_ident("borrow") {
+        flags |= ShrinkwrapFlags::SW_BORROW;
+      } else if path.is_ident("index") {
+        flags |= ShrinkwrapFlags::SW_INDEX;
       }
     }
   }
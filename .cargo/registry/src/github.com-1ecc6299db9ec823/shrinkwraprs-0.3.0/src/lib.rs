@@ -96,6 +96,21 @@
 //! input_buffer.push_str("some values");
 //! ...
 //! ```
+//!
+//! Enums can also derive `Shrinkwrap`, as long as every variant carries a
+//! field of the same type to deref to (named fields must additionally agree
+//! on name, or be marked `#[shrinkwrap(main_field)]` if a variant has more
+//! than one field). This derives `Deref`/`DerefMut` via a `match self { .. }`
+//! over the variants; `AsRef`/`Borrow`/`Index` etc. aren't derived for enums.
+//!
+//! ```ignore
+//! #[derive(Shrinkwrap)]
+//! #[shrinkwrap(mutable)]
+//! enum Token {
+//!     Keyword(String),
+//!     Identifier(String),
+//! }
+//! ```
 
 // Additionally, perhaps subsume some functionality from
 // [`from_variants`](https://crates.io/crates/from_variants)?
@@ -119,7 +134,7 @@ mod visibility;
 
 #[proc_macro_derive(Shrinkwrap, attributes(shrinkwrap))]
 pub fn shrinkwrap(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
-  use ast::{ShrinkwrapFlags, validate_derive_input};
+  use ast::{ShrinkwrapFlags, ShrinkwrapInput, validate_derive_input};
   use visibility::field_visibility;
   use visibility::FieldVisibility::*;
 
@@ -131,7 +146,16 @@ pub fn shrinkwrap(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
 
   match validate_result {
     Err(error) => error.to_tokens(&mut tokens),
-    Ok((details, input)) => {
+    Ok((details, ShrinkwrapInput::Enum(enum_common))) => {
+      impl_enum_deref(&details, &enum_common)
+        .to_tokens(&mut tokens);
+
+      if details.flags.contains(ShrinkwrapFlags::SW_MUT) {
+        impl_enum_deref_mut(&details, &enum_common)
+          .to_tokens(&mut tokens);
+      }
+    },
+    Ok((details, ShrinkwrapInput::Struct(input))) => {
       impl_immut_borrows(&details, &input)
         .to_tokens(&mut tokens);
 
@@ -140,6 +164,11 @@ pub fn shrinkwrap(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
           .to_tokens(&mut tokens);
       }
 
+      if details.flags.contains(ShrinkwrapFlags::SW_INDEX) {
+        impl_index(&details, &input)
+          .to_tokens(&mut tokens);
+      }
+
       if details.flags.contains(ShrinkwrapFlags::SW_MUT) {
         // Make sure that the inner field isn't less visible than the outer struct.
         if !details.flags.contains(ast::ShrinkwrapFlags::SW_IGNORE_VIS) {
@@ -227,6 +256,11 @@ Some ways to solve this problem:
 
         impl_mut_borrows(&details, &input)
           .to_tokens(&mut tokens);
+
+        if details.flags.contains(ShrinkwrapFlags::SW_INDEX) {
+          impl_index_mut(&details, &input)
+            .to_tokens(&mut tokens);
+        }
       }
     }
   }
@@ -300,6 +334,124 @@ fn impl_mut_borrows(details: &ast::StructDetails, input: &ast::Struct) -> TokenS
   }
 }
 
+// `Index`/`IndexMut` need an extra type parameter for whatever index type the
+// inner value accepts, which isn't one of the struct's own generics. We build
+// it by augmenting a clone of the struct's `Generics` rather than splicing
+// raw tokens, so this still works if the struct already has its own generics
+// and/or where clause.
+fn impl_index(details: &ast::StructDetails, input: &ast::Struct) -> TokenStream {
+  let &ast::StructDetails { ref ident, ref generics, .. } = details;
+  let &ast::Struct { ref inner_field, ref inner_field_name, .. } = input;
+
+  let inner_type = &inner_field.ty;
+  let rust = syn::Ident::new(RUST, Span::call_site());
+
+  // `ty_generics` must come from the struct's own, unmutated `generics` --
+  // only `impl_generics`/`where_clause` need the `__ShrinkwrapIdx` param
+  // added, since that's introduced by this impl, not by the struct itself.
+  // Splitting the augmented clone for `ty_generics` too would require
+  // callers to supply `__ShrinkwrapIdx` as if it were one of the struct's
+  // own generic arguments -- which is how a zero-generic struct ended up
+  // with an impl requiring one.
+  let (_, ty_generics, _) = generics.split_for_impl();
+
+  let mut augmented = generics.clone();
+  augmented.params.push(syn::parse_str("__ShrinkwrapIdx").unwrap());
+  augmented.make_where_clause().predicates.push(
+    syn::parse2(quote!( #inner_type: ::#rust::ops::Index<__ShrinkwrapIdx> )).unwrap()
+  );
+
+  let (impl_generics, _, where_clause) = augmented.split_for_impl();
+
+  quote! {
+    impl #impl_generics ::#rust::ops::Index<__ShrinkwrapIdx> for #ident #ty_generics #where_clause {
+      type Output = <#inner_type as ::#rust::ops::Index<__ShrinkwrapIdx>>::Output;
+
+      fn index(&self, index: __ShrinkwrapIdx) -> &Self::Output {
+        ::#rust::ops::Index::index(&self.#inner_field_name, index)
+      }
+    }
+  }
+}
+
+fn impl_index_mut(details: &ast::StructDetails, input: &ast::Struct) -> TokenStream {
+  let &ast::StructDetails { ref ident, ref generics, .. } = details;
+  let &ast::Struct { ref inner_field, ref inner_field_name, .. } = input;
+
+  let inner_type = &inner_field.ty;
+  let rust = syn::Ident::new(RUST, Span::call_site());
+
+  // See the matching comment in `impl_index`: `ty_generics` must come from
+  // the unmutated `generics`, not the `__ShrinkwrapIdx`-augmented clone.
+  let (_, ty_generics, _) = generics.split_for_impl();
+
+  let mut augmented = generics.clone();
+  augmented.params.push(syn::parse_str("__ShrinkwrapIdx").unwrap());
+  augmented.make_where_clause().predicates.push(
+    syn::parse2(quote!( #inner_type: ::#rust::ops::IndexMut<__ShrinkwrapIdx> )).unwrap()
+  );
+
+  let (impl_generics, _, where_clause) = augmented.split_for_impl();
+
+  quote! {
+    impl #impl_generics ::#rust::ops::IndexMut<__ShrinkwrapIdx> for #ident #ty_generics #where_clause {
+      fn index_mut(&mut self, index: __ShrinkwrapIdx) -> &mut Self::Output {
+        ::#rust::ops::IndexMut::index_mut(&mut self.#inner_field_name, index)
+      }
+    }
+  }
+}
+
+// For an enum, there's no single `self.#inner_field_name` to deref to --
+// each variant reaches its copy of the shared field differently, so we
+// dispatch with a `match self { .. }` built from each variant's `pattern()`.
+fn impl_enum_deref(details: &ast::StructDetails, input: &ast::EnumCommon) -> TokenStream {
+  let &ast::StructDetails { ref ident, ref generics, .. } = details;
+  let &ast::EnumCommon { ref inner_type, ref variants } = input;
+
+  let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+  let rust = syn::Ident::new(RUST, Span::call_site());
+
+  let arms = variants.iter().map(|variant| {
+    let pattern = variant.pattern(ident, false);
+    quote!( #pattern => __shrinkwrap_inner )
+  });
+
+  quote! {
+    impl #impl_generics ::#rust::ops::Deref for #ident #ty_generics #where_clause {
+      type Target = #inner_type;
+      fn deref(&self) -> &Self::Target {
+        match self {
+          #(#arms),*
+        }
+      }
+    }
+  }
+}
+
+fn impl_enum_deref_mut(details: &ast::StructDetails, input: &ast::EnumCommon) -> TokenStream {
+  let &ast::StructDetails { ref ident, ref generics, .. } = details;
+  let &ast::EnumCommon { ref variants, .. } = input;
+
+  let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+  let rust = syn::Ident::new(RUST, Span::call_site());
+
+  let arms = variants.iter().map(|variant| {
+    let pattern = variant.pattern(ident, true);
+    quote!( #pattern => __shrinkwrap_inner )
+  });
+
+  quote! {
+    impl #impl_generics ::#rust::ops::DerefMut for #ident #ty_generics #where_clause {
+      fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+          #(#arms),*
+        }
+      }
+    }
+  }
+}
+
 fn impl_transformers(details: &ast::StructDetails, input: &ast::Struct) -> TokenStream {
   let &ast::StructDetails { ref ident, ref generics, .. } = details;
   let &ast::Struct { ref inner_field, ref inner_field_name, .. } = input;
@@ -0,0 +1,159 @@
+use crate::game_session::{GameSession, PlayerStats, ResourceAmount, SessionState, SESSION_UPDATE_TAG};
+use hdk::prelude::*;
+
+// Link tag from a GameSession to its GameRounds, in chronological order.
+pub const SESSION_TO_ROUND_TAG: &str = "SESSION_ROUND";
+
+#[hdk_entry(id = "game_round", visibility = "public")]
+#[derive(Clone)]
+pub struct GameRound {
+    // Which round this is, starting at 0 for the dummy round created by new_session
+    pub round_num: u32,
+    // The GameSession this round belongs to
+    pub session: EntryHash,
+    // How much each player committed to spend this round
+    pub player_spends: PlayerStats,
+    // Resources left in the pool after this round's spends and regeneration were applied
+    pub resource_amount: ResourceAmount,
+}
+
+impl GameRound {
+    pub fn new(
+        round_num: u32,
+        session: EntryHash,
+        resource_amount: ResourceAmount,
+        player_spends: PlayerStats,
+    ) -> Self {
+        Self {
+            round_num,
+            session,
+            player_spends,
+            resource_amount,
+        }
+    }
+}
+
+/// Input for committing a round: the session it belongs to, the round that
+/// precedes it (round zero counts as the first "previous round"), and how
+/// much each player spent this round.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RoundCommitInput {
+    pub session: EntryHash,
+    pub previous_round: EntryHash,
+    pub player_spends: PlayerStats,
+}
+
+/// What committing a round produced: the new round's hash, the (possibly
+/// updated) session's hash, and the session's status after this round.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RoundCommitOutput {
+    pub round: EntryHash,
+    pub session: EntryHash,
+    pub status: SessionState,
+}
+
+fn get_game_round(round: EntryHash) -> ExternResult<GameRound> {
+    let element = get(round, GetOptions::default())?
+        .ok_or(WasmError::Guest("Could not find the GameRound entry".into()))?;
+    element
+        .entry()
+        .to_app_option()?
+        .ok_or(WasmError::Guest("The targeted entry is not a GameRound".into()))
+}
+
+// `pub(crate)` so `game_session::get_my_own_sessions_via_source_query` can
+// fetch the entry a `SESSION_UPDATE_TAG` link points to.
+pub(crate) fn get_game_session(session: EntryHash) -> ExternResult<GameSession> {
+    let element = get(session, GetOptions::default())?
+        .ok_or(WasmError::Guest("Could not find the GameSession entry".into()))?;
+    element
+        .entry()
+        .to_app_option()?
+        .ok_or(WasmError::Guest("The targeted entry is not a GameSession".into()))
+}
+
+// Applies the game's regeneration factor to whatever resources are left
+// after this round's spends, never letting the pool go negative.
+fn regenerate(pool_after_spends: ResourceAmount, regeneration_factor: f32) -> ResourceAmount {
+    let regenerated = (pool_after_spends.max(0) as f32) * regeneration_factor;
+    (regenerated.round() as ResourceAmount).max(0)
+}
+
+/// Commits a round: sums the players' spends, subtracts them from the pool
+/// left over from the previous round, applies the game's regeneration
+/// factor to what remains, and advances the session's scores and status
+/// accordingly. Writes a new GameSession entry linked from the old one so
+/// that `get_my_own_sessions_via_source_query` reflects the live status.
+#[hdk_extern]
+pub fn try_commit_round(input: RoundCommitInput) -> ExternResult<RoundCommitOutput> {
+    let previous_round = get_game_round(input.previous_round.clone())?;
+    let session = get_game_session(input.session.clone())?;
+
+    // The updated GameSession is written to *this* call's source chain, and
+    // `get_my_own_sessions_via_source_query` assumes that chain only ever
+    // holds the owner's own games -- so only the owner may advance a session.
+    let caller = agent_info()?.agent_initial_pubkey;
+    if caller != session.owner {
+        return Err(WasmError::Guest(
+            "Only the session's owner can commit a round".into(),
+        ));
+    }
+
+    let total_spent: ResourceAmount = input.player_spends.values().sum();
+    let pool_after_spends = previous_round.resource_amount - total_spent;
+    let resource_amount = regenerate(pool_after_spends, session.game_params.regeneration_factor);
+    let round_num = previous_round.round_num + 1;
+
+    let round = GameRound::new(
+        round_num,
+        input.session.clone(),
+        resource_amount,
+        input.player_spends.clone(),
+    );
+    create_entry(&round)?;
+    let round_hash = hash_entry(&round)?;
+
+    create_link(
+        input.session.clone(),
+        round_hash.clone(),
+        LinkTag::new(SESSION_TO_ROUND_TAG),
+    )?;
+
+    // Carry every player's spend this round into their running total score
+    let mut scores = session.scores.clone();
+    for (player, spent) in input.player_spends.iter() {
+        *scores.entry(player.clone()).or_insert(0) += spent;
+    }
+
+    let status = if pool_after_spends <= 0 {
+        SessionState::Lost {
+            last_round: round_hash.clone(),
+        }
+    } else if round_num >= session.game_params.num_rounds {
+        SessionState::Finished {
+            last_round: round_hash.clone(),
+        }
+    } else {
+        SessionState::InProgress
+    };
+
+    let updated_session = GameSession {
+        scores,
+        status: status.clone(),
+        ..session
+    };
+    create_entry(&updated_session)?;
+    let updated_session_hash = hash_entry(&updated_session)?;
+
+    create_link(
+        input.session.clone(),
+        updated_session_hash.clone(),
+        LinkTag::new(SESSION_UPDATE_TAG),
+    )?;
+
+    Ok(RoundCommitOutput {
+        round: round_hash,
+        session: updated_session_hash,
+        status,
+    })
+}
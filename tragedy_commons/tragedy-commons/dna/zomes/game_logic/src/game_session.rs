@@ -1,4 +1,8 @@
-use crate::{game_code::get_game_code_anchor, player_profile::get_player_profile_for_game_code};
+use crate::{
+    game_code::get_game_code_anchor,
+    game_round::{get_game_session, GameRound, SESSION_TO_ROUND_TAG},
+    player_profile::get_player_profile_for_game_code,
+};
 use hdk::prelude::*;
 use std::collections::BTreeMap;
 
@@ -45,6 +49,9 @@ pub struct GameSession {
 
 pub const OWNER_SESSION_TAG: &str = "MY_GAMES";
 pub const GAME_CODE_TO_SESSION_TAG: &str = "GAME_SESSION";
+// Link tag from an old GameSession entry to the new version written whenever
+// a round is committed, so the live status can be found from the original.
+pub const SESSION_UPDATE_TAG: &str = "GAME_SESSION_UPDATE";
 
 /// Collects input info for the GameSession and calls new_session
 pub fn start_game_session_with_code(game_code: String) -> ExternResult<EntryHash> {
@@ -98,13 +105,12 @@ pub fn new_session(
         LinkTag::new(GAME_CODE_TO_SESSION_TAG),
     )?;
 
-    // Create a round zero: a dummy round we'll need to collect moves
+    // Create a round zero: a dummy round we'll need to collect moves, with
+    // the full starting pool and no spends yet
     let round_zero = GameRound::new(
         0,
         game_session_entry_hash.clone(),
         game_session.game_params.start_amount,
-        0,
-        0,
         PlayerStats::new(),
     );
     // Commit round_zero to DHT
@@ -121,18 +127,43 @@ pub fn new_session(
         LinkTag::new(SESSION_TO_ROUND_TAG),
     )?;
 
-
-    // For now, return the game session entry hash
-    // Once we implement a GameRound, we'll be doing more in this fn
+    // Further rounds are committed via game_round::try_commit_round
     Ok(game_session_entry_hash)
 }
 
+// Follows `SESSION_UPDATE_TAG` links from `hash` for as long as
+// `try_commit_round` has written a newer version, so callers end up with the
+// live session rather than whichever version the source chain query happened
+// to hand back.
+fn follow_to_latest_session(
+    hash: EntryHash,
+    session: GameSession,
+) -> ExternResult<(EntryHash, GameSession)> {
+    let mut current = (hash, session);
+    loop {
+        let links = get_links(current.0.clone(), Some(LinkTag::new(SESSION_UPDATE_TAG)))?;
+        let next_hash = match links.into_inner().pop() {
+            Some(link) => link.target,
+            None => break,
+        };
+        let next_session = get_game_session(next_hash.clone())?;
+        current = (next_hash, next_session);
+    }
+    Ok(current)
+}
+
 /// Queries source chain contents of the agent executing this fn
 /// Since game owner is the one creating the GameSession, they'll have all their games
 /// on the source chain already, so there's no need to go to network for this.
 /// This fns returns a tuple of (EntryHash, GameSession) for every game session:
 /// this is to make sure that UI would have both the data to display
 /// and it's hash to identify the corresponding Holochain entry for any other actions
+///
+/// The source chain holds every version `try_commit_round` ever wrote for a
+/// session, not just the latest, so each entry is first followed forward via
+/// `SESSION_UPDATE_TAG` to its current version, and the results deduplicated
+/// by that final hash (a session updated N times would otherwise appear
+/// N+1 times).
 pub fn get_my_own_sessions_via_source_query() -> ExternResult<Vec<(EntryHash, GameSession)>> {
     // Create a new filter instance that would define query we want to execute
     let filter = ChainQueryFilter::new()
@@ -165,7 +196,17 @@ pub fn get_my_own_sessions_via_source_query() -> ExternResult<Vec<(EntryHash, Ga
         // Add a tuple with entry hash and actual entry to our results list
         list_of_tuples.push((gs_hash.clone(), gs));
     }
-    Ok(list_of_tuples)
+
+    // Resolve every version to its latest, then dedupe: several entries in
+    // `list_of_tuples` can follow to the same final (hash, GameSession) if
+    // `try_commit_round` has advanced that session more than once.
+    let mut latest = BTreeMap::new();
+    for (hash, session) in list_of_tuples {
+        let (latest_hash, latest_session) = follow_to_latest_session(hash, session)?;
+        latest.insert(latest_hash, latest_session);
+    }
+
+    Ok(latest.into_iter().collect())
 }
 
 
@@ -3,24 +3,192 @@ use hdk::prelude::*;
 //Anchor
 pub const  GAME_CODES_ANCHOR: &str = "GAME_CODES";
 
+// Prefijo legible que antecede a todo game code generado por `encode_game_code`.
+const GAME_CODE_HRP: &str = "game";
+
+// Alfabeto bech32: 32 caracteres que omiten los ambiguos 1/b/i/o.
+const CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+// Constantes generadoras del polymod de bech32 (BIP-173).
+const GENERATOR: [u32; 5] = [
+    0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3,
+];
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut chk: u32 = 1;
+    for &v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ff_ffff) << 5) ^ (v as u32);
+        for (i, gen) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= gen;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut v: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    v.push(0);
+    v.extend(hrp.bytes().map(|b| b & 31));
+    v
+}
+
+// Calcula los 6 símbolos de checksum que se añaden tras los datos.
+fn create_checksum(hrp: &str, data: &[u8]) -> Vec<u8> {
+    let mut values = hrp_expand(hrp);
+    values.extend_from_slice(data);
+    values.extend_from_slice(&[0u8; 6]);
+    let mod_ = polymod(&values) ^ 1;
+    (0..6)
+        .map(|i| ((mod_ >> (5 * (5 - i))) & 31) as u8)
+        .collect()
+}
+
+fn verify_checksum(hrp: &str, data: &[u8]) -> bool {
+    let mut values = hrp_expand(hrp);
+    values.extend_from_slice(data);
+    polymod(&values) == 1
+}
+
+// Reempaqueta una secuencia de palabras de `from_bits` bits en palabras de
+// `to_bits` bits, igual que hace bech32 al pasar de bytes (8 bits) a
+// símbolos del alfabeto (5 bits) y viceversa.
+fn convert_bits(data: &[u8], from_bits: u32, to_bits: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let maxv: u32 = (1 << to_bits) - 1;
+    let mut ret = Vec::new();
+    for &value in data {
+        if (value as u32) >> from_bits != 0 {
+            return None;
+        }
+        acc = (acc << from_bits) | value as u32;
+        bits += from_bits;
+        while bits >= to_bits {
+            bits -= to_bits;
+            ret.push(((acc >> bits) & maxv) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            ret.push(((acc << (to_bits - bits)) & maxv) as u8);
+        }
+    } else if bits >= from_bits || ((acc << (to_bits - bits)) & maxv) != 0 {
+        return None;
+    }
+    Some(ret)
+}
+
+/// Codifica bytes de partida en bruto (p.ej. entropía de `random_bytes`) como
+/// un código bech32 con prefijo `"game"` y un checksum de 6 símbolos, para
+/// que un error de tecleo (o una transposición de dos caracteres) se pueda
+/// detectar antes de llegar al DHT.
+pub fn encode_game_code(raw: &[u8]) -> String {
+    // `pad: true` con entrada/salida de 8/5 bits nunca puede fallar.
+    let data = convert_bits(raw, 8, 5, true).expect("convert_bits with pad cannot fail");
+    let checksum = create_checksum(GAME_CODE_HRP, &data);
+
+    let payload: String = data
+        .iter()
+        .chain(checksum.iter())
+        .map(|&symbol| CHARSET[symbol as usize] as char)
+        .collect();
+
+    format!("{}1{}", GAME_CODE_HRP, payload)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decodifica y valida un código generado por [`encode_game_code`], devolviendo
+/// los bytes en bruto originales como una cadena hexadecimal (la entropía de
+/// origen no tiene por qué ser UTF-8 válido, así que no podemos devolverla tal
+/// cual). Si el checksum no coincide (típicamente por un error de tecleo),
+/// devuelve un error en vez de dejar que la búsqueda falle silenciosamente
+/// contra el anchor equivocado.
+pub fn decode_game_code(code: &str) -> ExternResult<String> {
+    let lowered = code.to_lowercase();
+    let separator = lowered
+        .rfind('1')
+        .ok_or(WasmError::Guest("game code is missing the '1' separator".into()))?;
+
+    let (hrp, rest) = lowered.split_at(separator);
+    let data_part = &rest[1..];
+
+    if hrp != GAME_CODE_HRP {
+        return Err(WasmError::Guest(format!(
+            "game code has unexpected prefix '{}', expected '{}'",
+            hrp, GAME_CODE_HRP
+        )));
+    }
+    if data_part.len() < 6 {
+        return Err(WasmError::Guest(
+            "game code is too short to contain a checksum".into(),
+        ));
+    }
+
+    let mut values = Vec::with_capacity(data_part.len());
+    for c in data_part.chars() {
+        let symbol = CHARSET
+            .iter()
+            .position(|&x| x as char == c)
+            .ok_or_else(|| WasmError::Guest(format!("game code contains an invalid character '{}'", c)))?;
+        values.push(symbol as u8);
+    }
+
+    if !verify_checksum(GAME_CODE_HRP, &values) {
+        return Err(WasmError::Guest(
+            "game code checksum does not match -- check for a typo".into(),
+        ));
+    }
+
+    let data = &values[..values.len() - 6];
+    let raw_bytes = convert_bits(data, 5, 8, false)
+        .ok_or(WasmError::Guest("game code payload is malformed".into()))?;
+
+    Ok(to_hex(&raw_bytes))
+}
+
 //Objetivo de la función:
 //1.- Con el hard_code del anchor principal, el primer usuario crea un anchor para la partida.
-//2.- Recibe un string shor_unique_code --que introduce el agente -- y devuelve un hash o un error
+//2.- Recibe un string shor_unique_code --el código codificado que introduce el agente, que aquí se decodifica y se verifica con decode_game_code-- y devuelve un hash o un error
 pub fn create_game_code_anchor(short_unique_code: String) -> ExternResult<EntryHash> {
 
-    //Se crea la variable anchor que linka la constante GAME_CODES_ANCHOR con short_unique_code
+    let canonical_code = decode_game_code(&short_unique_code)?;
+
+    //Se crea la variable anchor que linka la constante GAME_CODES_ANCHOR con canonical_code
     //como GAME_CODES_ANCHOR es tipo &str se usa into() para convertir al tipo que necesita anchor()
-    let anchor = anchor(GAME_CODES_ANCHOR.into(), short_unique_code)?;
+    let anchor = anchor(GAME_CODES_ANCHOR.into(), canonical_code)?;
 
     Ok(anchor)
 }
 
 //El segundo jugador se une a la partida. Debe encontrat el anchor
-//game_code es una entrada del segundo jugador.
+//game_code es el código codificado que introduce el segundo jugador; aquí se decodifica y se verifica con decode_game_code.
 pub fn get_game_code_anchor(game_code: String) -> ExternResult<EntryHash> {
 
+    let canonical_code = decode_game_code(&game_code)?;
+
     //la función devuelve el hash creado por anchor()->
-    anchor(GAME_CODES_ANCHOR.into(), game_code.clone())
+    anchor(GAME_CODES_ANCHOR.into(), canonical_code)
+
+
+}
+
+/// Genera un código de partida nuevo a partir de bytes aleatorios del host,
+/// lo codifica con [`encode_game_code`] (el mismo checksum bech32 que
+/// [`decode_game_code`] verifica) y crea su anchor reutilizando
+/// [`create_game_code_anchor`], igual que haría el segundo jugador al
+/// unirse. Devuelve el código legible para compartir junto con el hash del
+/// anchor.
+pub fn create_new_game_code_anchor() -> ExternResult<(String, EntryHash)> {
+    let raw_bytes = random_bytes(4)?;
+    let short_unique_code = encode_game_code(&raw_bytes.into_vec());
 
+    let anchor = create_game_code_anchor(short_unique_code.clone())?;
 
+    Ok((short_unique_code, anchor))
 }
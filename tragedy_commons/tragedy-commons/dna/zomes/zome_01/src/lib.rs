@@ -3,9 +3,12 @@ use hdk::prelude::holo_hash::*;
 
 //se importa el script
 mod game_code;
+mod player_profile;
+
+use player_profile::PlayerProfile;
 
 entry_defs![
-    
+    PlayerProfile::entry_def()
 ];
 
 //esto es el wrapper -> se importa la función
@@ -14,6 +17,14 @@ pub fn create_game_code_anchor(short_unique_code: String) -> ExternResult<EntryH
 
     game_code::create_game_code_anchor(short_unique_code)
 }
+
+//el primer jugador no introduce ningun codigo, se genera aqui y se devuelve
+//ya codificado para que lo comparta con el resto
+#[hdk_extern]
+pub fn create_new_game_code_anchor() -> ExternResult<(String, EntryHash)> {
+
+    game_code::create_new_game_code_anchor()
+}
 //cada función...
 #[hdk_extern]
 pub fn get_game_code_anchor(game_code: String) -> ExternResult<EntryHash> {
@@ -22,3 +33,9 @@ pub fn get_game_code_anchor(game_code: String) -> ExternResult<EntryHash> {
 
 }
 
+#[hdk_extern]
+pub fn validate(data: ValidateData) -> ExternResult<ValidateCallbackResult> {
+
+    player_profile::validate_player_profile(data)
+}
+
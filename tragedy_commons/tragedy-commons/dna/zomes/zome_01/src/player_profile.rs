@@ -1,11 +1,15 @@
 use hdk::prelude::*;
 use crate::game_code::get_game_code_anchor;
+use contrafact::{arbitrary::Unstructured, facts, lens, Check, CheckTree, Fact, Facts, Mutation};
 
 pub const PLAYER_LINK_TAG: &str = "PLAYER";
 
+/// Longitud maxima permitida para un nickname, usada por [`NicknameFact`].
+const MAX_NICKNAME_LEN: usize = 40;
+
 //Holochain provee con hdk_entry la public_key y la hace publica
 #[hdk_entry(id = "player_profile", visibility = "public")]
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, contrafact::arbitrary::Arbitrary)]
 //player_id lo crea el backend y es la public key del agente dentro de la app
 //esta en la sourcechain
 //nickname lo introduce el usuario desde la UI
@@ -14,6 +18,89 @@ pub struct  PlayerProfile {
     pub nickname: String,
 }
 
+impl PlayerProfile {
+    /// The invariants the `validate` callback enforces: `player_id` must be
+    /// the agent that wrote the entry, and `nickname` must satisfy
+    /// [`NicknameFact`]. Reused both by `validate_player_profile` and by
+    /// tests, so there's one source of truth for what a valid `PlayerProfile`
+    /// looks like.
+    pub fn fact<'a>(agent_initial_pubkey: AgentPubKey) -> Facts<'a, PlayerProfile> {
+        facts![
+            lens(
+                "PlayerProfile::player_id",
+                |p: &mut PlayerProfile| &mut p.player_id,
+                contrafact::eq("player_id must match the writing agent", agent_initial_pubkey),
+            ),
+            lens(
+                "PlayerProfile::nickname",
+                |p: &mut PlayerProfile| &mut p.nickname,
+                NicknameFact,
+            ),
+        ]
+    }
+}
+
+/// Fact asserting a nickname is non-empty, no longer than
+/// [`MAX_NICKNAME_LEN`] characters, and free of control characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NicknameFact;
+
+impl<'a> Fact<'a, String> for NicknameFact {
+    fn check(&self, nickname: &String) -> Check {
+        let mut errors: Vec<CheckTree> = Vec::new();
+        errors.extend(Check::check(!nickname.is_empty(), "nickname must not be empty"));
+        errors.extend(Check::check(
+            nickname.chars().count() <= MAX_NICKNAME_LEN,
+            format!("nickname must be at most {} characters", MAX_NICKNAME_LEN),
+        ));
+        errors.extend(Check::check(
+            !nickname.chars().any(|c| c.is_control()),
+            "nickname must not contain control characters",
+        ));
+        errors.into()
+    }
+
+    fn mutate(&mut self, nickname: &mut String, u: &mut Unstructured<'a>, m: &mut Mutation) {
+        let violates = nickname.is_empty()
+            || nickname.chars().count() > MAX_NICKNAME_LEN
+            || nickname.chars().any(|c| c.is_control());
+        if violates {
+            let len = u.int_in_range(1..=MAX_NICKNAME_LEN).unwrap();
+            *nickname = (0..len)
+                .map(|_| (b'a' + u.int_in_range(0..=25u8).unwrap()) as char)
+                .collect();
+            m.mark_changed();
+        }
+    }
+
+    fn advance(&mut self, _: &String) {}
+
+    fn is_stateful(&self) -> bool {
+        false
+    }
+}
+
+//valida que la entrada PlayerProfile cumpla sus invariantes: que player_id
+//sea la clave publica del agente que la escribe, y que el nickname sea
+//valido segun NicknameFact. Usa el mismo Fact que generan y comprueban los
+//tests, asi que hay una sola fuente de verdad para lo que hace valido a un
+//PlayerProfile.
+pub fn validate_player_profile(data: ValidateData) -> ExternResult<ValidateCallbackResult> {
+    let profile: PlayerProfile = match data.element.entry().to_app_option()? {
+        Some(profile) => profile,
+        // No es un PlayerProfile -> este callback no tiene nada que decir.
+        None => return Ok(ValidateCallbackResult::Valid),
+    };
+
+    let author = data.element.header().author().clone();
+    let check = PlayerProfile::fact(author).check(&profile);
+
+    match check.result() {
+        Ok(()) => Ok(ValidateCallbackResult::Valid),
+        Err(errors) => Ok(ValidateCallbackResult::Invalid(errors.join("; "))),
+    }
+}
+
 //wrapper de la info de la partida para fn join_game_with_code
 //¿¿¿¿?????
 pub struct JoinGameInfo {
@@ -89,4 +176,54 @@ pub fn get_player_profile_for_game_code(
         players.push(entry);
     }
     Ok(players)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn an_agent() -> AgentPubKey {
+        AgentPubKey::from_raw_36(vec![1; 36])
+    }
+
+    #[test]
+    fn fact_builds_a_profile_that_satisfies_itself() {
+        let agent = an_agent();
+        let mut u = Unstructured::new(&[0; 9999]);
+        let mut fact = PlayerProfile::fact(agent.clone());
+        let profile = fact.build(&mut u).unwrap();
+        assert!(PlayerProfile::fact(agent).check(&profile).is_ok());
+    }
+
+    #[test]
+    fn fact_rejects_a_profile_written_by_a_different_agent() {
+        let profile = PlayerProfile {
+            player_id: an_agent(),
+            nickname: "a_valid_nickname".into(),
+        };
+        let someone_else = AgentPubKey::from_raw_36(vec![2; 36]);
+        assert!(PlayerProfile::fact(someone_else).check(&profile).is_err());
+    }
+
+    #[test]
+    fn nickname_fact_accepts_a_normal_nickname() {
+        assert!(NicknameFact.check(&"a_valid_nickname".to_string()).is_ok());
+    }
+
+    #[test]
+    fn nickname_fact_rejects_an_empty_nickname() {
+        assert!(NicknameFact.check(&String::new()).is_err());
+    }
+
+    #[test]
+    fn nickname_fact_rejects_an_oversized_nickname() {
+        let too_long = "a".repeat(MAX_NICKNAME_LEN + 1);
+        assert!(NicknameFact.check(&too_long).is_err());
+    }
+
+    #[test]
+    fn nickname_fact_rejects_control_characters() {
+        let with_control = "bad\u{0007}nick".to_string();
+        assert!(NicknameFact.check(&with_control).is_err());
+    }
 }
\ No newline at end of file